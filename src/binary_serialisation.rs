@@ -0,0 +1,518 @@
+// A binary format for the SRS and update proofs.
+//
+// The JSON format stores every point as a hex string, which roughly doubles the size
+// and is slow to parse for large ceremonies. Here points are stored as raw bytes
+// in the same encoding as the JSON format, so an SRS can be streamed to and from a
+// file or socket.
+//
+// Each object starts with a header:
+// - 4 magic bytes identifying the object
+// - 1 byte for the format version
+// followed by object specific fields. Integers are little-endian.
+//
+// SRS: header, num G1 powers (u64), num G2 powers (u64), encoding flag (u8), G1 points, G2 points
+// UpdateProof: header, encoding flag (u8), commitment to secret (G2), new accumulated point (G1)
+// Witness: header, encoding flag (u8), num entries (u64), running products (G1), pot pubkeys (G2),
+//          bls signatures (a u8 presence flag, followed by the G1 point if present)
+// Transcript: header, num ceremonies (u64), an SRS and witness for each ceremony,
+//             participant ids, participant ECDSA signatures
+// Checkpoint: header, encoding flag (u8), num G1 powers (u64), num G2 powers (u64),
+//             points done (u64), public key (G2), new accumulated point (a u8 presence flag,
+//             followed by the G1 point if present)
+// Lists of strings are written as their length (u64), followed by each string as its
+// length in bytes (u64) and its UTF-8 bytes
+//
+// Wrap files in a `BufReader`/`BufWriter`, as points are read and written one at a time.
+use std::io::{Read, Write};
+
+use crate::{
+    chunked_update::Checkpoint,
+    error::{DeserialiseError, Group, PointDeserialiseError},
+    interop_point_encoding::{
+        g1_from_reader, g1_to_writer, g2_from_reader, g2_to_writer, PointEncoding,
+    },
+    keypair::PublicKey,
+    srs::{Parameters, SRS},
+    update_proof::UpdateProof,
+    witness::Witness,
+};
+use ark_bls12_381::{G1Projective, G2Projective};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+
+pub const FORMAT_VERSION: u8 = 1;
+
+pub(crate) const SRS_MAGIC: [u8; 4] = *b"PTAU";
+pub(crate) const UPDATE_PROOF_MAGIC: [u8; 4] = *b"PTUP";
+pub(crate) const TRANSCRIPT_MAGIC: [u8; 4] = *b"PTTR";
+pub(crate) const WITNESS_MAGIC: [u8; 4] = *b"PTWT";
+pub(crate) const CHECKPOINT_MAGIC: [u8; 4] = *b"PTCK";
+
+// Upper bound on the number of points that we allocate space for before reading them.
+// The header is not trusted, so larger SRS's grow their vectors as points are read.
+const MAX_PREALLOCATED_POINTS: usize = 1 << 16;
+
+pub(crate) fn write_header<W: Write>(writer: &mut W, magic: [u8; 4]) -> std::io::Result<()> {
+    writer.write_all(&magic)?;
+    writer.write_all(&[FORMAT_VERSION])
+}
+
+pub(crate) fn read_header<R: Read>(reader: &mut R, magic: [u8; 4]) -> Result<(), DeserialiseError> {
+    let mut got_magic = [0u8; 4];
+    read_bytes(reader, &mut got_magic)?;
+    if got_magic != magic {
+        return Err(DeserialiseError::InvalidMagic);
+    }
+
+    let version = read_u8(reader)?;
+    if version != FORMAT_VERSION {
+        return Err(DeserialiseError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+pub(crate) fn write_u64<W: Write>(writer: &mut W, value: u64) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> Result<u64, DeserialiseError> {
+    let mut bytes = [0u8; 8];
+    read_bytes(reader, &mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8, DeserialiseError> {
+    let mut bytes = [0u8; 1];
+    read_bytes(reader, &mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_bytes<R: Read>(reader: &mut R, bytes: &mut [u8]) -> Result<(), DeserialiseError> {
+    reader
+        .read_exact(bytes)
+        .map_err(|err| DeserialiseError::Io(err.kind()))
+}
+
+pub(crate) fn write_strings<W: Write>(writer: &mut W, strings: &[String]) -> std::io::Result<()> {
+    write_u64(writer, strings.len() as u64)?;
+    for string in strings {
+        write_u64(writer, string.len() as u64)?;
+        writer.write_all(string.as_bytes())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_strings<R: Read>(reader: &mut R) -> Result<Vec<String>, DeserialiseError> {
+    let num_strings = read_u64(reader)?;
+
+    let mut strings = Vec::new();
+    for _ in 0..num_strings {
+        let len = read_u64(reader)?;
+        // Read through `take` so that a bad length does not allocate more than is available
+        let mut bytes = Vec::new();
+        reader
+            .take(len)
+            .read_to_end(&mut bytes)
+            .map_err(|err| DeserialiseError::Io(err.kind()))?;
+        if bytes.len() as u64 != len {
+            return Err(DeserialiseError::Io(std::io::ErrorKind::UnexpectedEof));
+        }
+        let string = String::from_utf8(bytes).map_err(|_| DeserialiseError::InvalidUtf8)?;
+        strings.push(string);
+    }
+    Ok(strings)
+}
+
+pub(crate) fn write_encoding<W: Write>(
+    writer: &mut W,
+    encoding: PointEncoding,
+) -> std::io::Result<()> {
+    let flag = match encoding {
+        PointEncoding::Compressed => 0u8,
+        PointEncoding::Uncompressed => 1u8,
+    };
+    writer.write_all(&[flag])
+}
+
+pub(crate) fn read_encoding<R: Read>(reader: &mut R) -> Result<PointEncoding, DeserialiseError> {
+    match read_u8(reader)? {
+        0 => Ok(PointEncoding::Compressed),
+        1 => Ok(PointEncoding::Uncompressed),
+        flag => Err(DeserialiseError::InvalidEncodingFlag(flag)),
+    }
+}
+
+pub(crate) fn read_num_powers<R: Read>(
+    reader: &mut R,
+    group: Group,
+) -> Result<usize, DeserialiseError> {
+    let num_powers = read_u64(reader)?;
+    // An SRS needs at least two elements in each group
+    if num_powers < 2 {
+        return Err(DeserialiseError::NotEnoughPowers {
+            group,
+            got: num_powers as usize,
+        });
+    }
+    usize::try_from(num_powers).map_err(|_| DeserialiseError::NotEnoughPowers {
+        group,
+        got: usize::MAX,
+    })
+}
+
+impl SRS {
+    // Writes the SRS in the binary format, using the given point encoding
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: PointEncoding,
+    ) -> std::io::Result<()> {
+        let g1s = self.g1_elements();
+        let g2s = self.g2_elements();
+
+        write_header(writer, SRS_MAGIC)?;
+        write_u64(writer, g1s.len() as u64)?;
+        write_u64(writer, g2s.len() as u64)?;
+        write_encoding(writer, encoding)?;
+
+        for point in G1Projective::batch_normalization_into_affine(g1s) {
+            g1_to_writer(writer, &point, encoding)?;
+        }
+        for point in G2Projective::batch_normalization_into_affine(g2s) {
+            g2_to_writer(writer, &point, encoding)?;
+        }
+        Ok(())
+    }
+
+    // Reads an SRS in the binary format
+    //
+    // Like `deserialise`, we check that every point is on the curve, but not
+    // that it is in the prime order subgroup
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<SRS, DeserialiseError> {
+        SRS::read_with_parameters(reader, None)
+    }
+
+    // Like `read_from`, but when `parameters` is given the number of powers in the header
+    // must match it, which is checked before any point is read
+    pub(crate) fn read_with_parameters<R: Read>(
+        reader: &mut R,
+        parameters: Option<Parameters>,
+    ) -> Result<SRS, DeserialiseError> {
+        read_header(reader, SRS_MAGIC)?;
+        let num_g1_powers = read_num_powers(reader, Group::G1)?;
+        let num_g2_powers = read_num_powers(reader, Group::G2)?;
+        if let Some(parameters) = parameters {
+            let groups = [
+                (Group::G1, parameters.num_g1_elements_needed, num_g1_powers),
+                (Group::G2, parameters.num_g2_elements_needed, num_g2_powers),
+            ];
+            for (group, expected, got) in groups {
+                if expected != got {
+                    return Err(DeserialiseError::WrongNumberOfPowers {
+                        group,
+                        expected,
+                        got,
+                    });
+                }
+            }
+        }
+        let encoding = read_encoding(reader)?;
+
+        let mut g1s = Vec::with_capacity(num_g1_powers.min(MAX_PREALLOCATED_POINTS));
+        for index in 0..num_g1_powers {
+            let point = g1_from_reader(reader, encoding).map_err(|error| {
+                DeserialiseError::InvalidPoint {
+                    group: Group::G1,
+                    index,
+                    error,
+                }
+            })?;
+            g1s.push(point.into_projective());
+        }
+
+        let mut g2s = Vec::with_capacity(num_g2_powers.min(MAX_PREALLOCATED_POINTS));
+        for index in 0..num_g2_powers {
+            let point = g2_from_reader(reader, encoding).map_err(|error| {
+                DeserialiseError::InvalidPoint {
+                    group: Group::G2,
+                    index,
+                    error,
+                }
+            })?;
+            g2s.push(point.into_projective());
+        }
+
+        // `from_vectors` only fails when there are less than two powers in a group
+        // which was checked when reading the header
+        Ok(SRS::from_vectors(g1s, g2s).expect("srs has at least two powers in each group"))
+    }
+}
+
+impl UpdateProof {
+    // Writes the update proof in the binary format, using the given point encoding
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: PointEncoding,
+    ) -> std::io::Result<()> {
+        write_header(writer, UPDATE_PROOF_MAGIC)?;
+        write_encoding(writer, encoding)?;
+        g2_to_writer(writer, &self.commitment_to_secret.into_affine(), encoding)?;
+        g1_to_writer(writer, &self.new_accumulated_point.into_affine(), encoding)
+    }
+
+    // Reads an update proof in the binary format
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<UpdateProof, DeserialiseError> {
+        read_header(reader, UPDATE_PROOF_MAGIC)?;
+        let encoding = read_encoding(reader)?;
+
+        // An update proof holds a single point in each group, so the index is always zero
+        let commitment_to_secret =
+            g2_from_reader(reader, encoding).map_err(|error| DeserialiseError::InvalidPoint {
+                group: Group::G2,
+                index: 0,
+                error,
+            })?;
+        let new_accumulated_point =
+            g1_from_reader(reader, encoding).map_err(|error| DeserialiseError::InvalidPoint {
+                group: Group::G1,
+                index: 0,
+                error,
+            })?;
+
+        Ok(UpdateProof {
+            commitment_to_secret: commitment_to_secret.into_projective(),
+            new_accumulated_point: new_accumulated_point.into_projective(),
+        })
+    }
+}
+
+impl Witness {
+    // Writes the witness in the binary format, using the given point encoding
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: PointEncoding,
+    ) -> std::io::Result<()> {
+        write_header(writer, WITNESS_MAGIC)?;
+        write_encoding(writer, encoding)?;
+        write_u64(writer, self.running_products.len() as u64)?;
+
+        for point in G1Projective::batch_normalization_into_affine(&self.running_products) {
+            g1_to_writer(writer, &point, encoding)?;
+        }
+        for point in G2Projective::batch_normalization_into_affine(&self.pot_pubkeys) {
+            g2_to_writer(writer, &point, encoding)?;
+        }
+        for signature in &self.bls_signatures {
+            match signature {
+                Some(signature) => {
+                    writer.write_all(&[1])?;
+                    g1_to_writer(writer, &signature.into_affine(), encoding)?;
+                }
+                None => writer.write_all(&[0])?,
+            }
+        }
+        Ok(())
+    }
+
+    // Reads a witness in the binary format
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Witness, DeserialiseError> {
+        read_header(reader, WITNESS_MAGIC)?;
+        let encoding = read_encoding(reader)?;
+        let num_entries = read_u64(reader)? as usize;
+
+        let g1_error = |index, error| DeserialiseError::InvalidPoint {
+            group: Group::G1,
+            index,
+            error,
+        };
+
+        let mut running_products = Vec::with_capacity(num_entries.min(MAX_PREALLOCATED_POINTS));
+        for index in 0..num_entries {
+            let point = g1_from_reader(reader, encoding).map_err(|error| g1_error(index, error))?;
+            running_products.push(point.into_projective());
+        }
+        let mut pot_pubkeys = Vec::with_capacity(num_entries.min(MAX_PREALLOCATED_POINTS));
+        for index in 0..num_entries {
+            let point = g2_from_reader(reader, encoding).map_err(|error| {
+                DeserialiseError::InvalidPoint {
+                    group: Group::G2,
+                    index,
+                    error,
+                }
+            })?;
+            pot_pubkeys.push(point.into_projective());
+        }
+        let mut bls_signatures = Vec::with_capacity(num_entries.min(MAX_PREALLOCATED_POINTS));
+        for index in 0..num_entries {
+            let signature = match read_u8(reader)? {
+                0 => None,
+                1 => {
+                    let point =
+                        g1_from_reader(reader, encoding).map_err(|error| g1_error(index, error))?;
+                    Some(point.into_projective())
+                }
+                _ => {
+                    return Err(g1_error(index, PointDeserialiseError::InvalidFlags));
+                }
+            };
+            bls_signatures.push(signature);
+        }
+
+        Ok(Witness {
+            running_products,
+            pot_pubkeys,
+            bls_signatures,
+        })
+    }
+}
+
+impl Checkpoint {
+    // Writes the checkpoint in the binary format, using the same point encoding as the SRS
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        // The counts and the encoding are in the same order as the header of an SRS
+        write_header(writer, CHECKPOINT_MAGIC)?;
+        write_u64(writer, self.num_g1_powers as u64)?;
+        write_u64(writer, self.num_g2_powers as u64)?;
+        write_encoding(writer, self.encoding)?;
+        write_u64(writer, self.points_done as u64)?;
+        g2_to_writer(
+            writer,
+            &self.public_key.point().into_affine(),
+            self.encoding,
+        )?;
+        match self.new_accumulated_point {
+            Some(point) => {
+                writer.write_all(&[1])?;
+                g1_to_writer(writer, &point.into_affine(), self.encoding)
+            }
+            None => writer.write_all(&[0]),
+        }
+    }
+
+    // Reads a checkpoint in the binary format
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Checkpoint, DeserialiseError> {
+        read_header(reader, CHECKPOINT_MAGIC)?;
+        let num_g1_powers = read_num_powers(reader, Group::G1)?;
+        let num_g2_powers = read_num_powers(reader, Group::G2)?;
+        let encoding = read_encoding(reader)?;
+        let points_done = read_u64(reader)? as usize;
+
+        // A checkpoint holds a single point in each group, so the index is always zero
+        let public_key = g2_from_reader(reader, encoding)
+            .and_then(|point| {
+                PublicKey::new(point.into_projective()).ok_or(PointDeserialiseError::NotInSubgroup)
+            })
+            .map_err(|error| DeserialiseError::InvalidPoint {
+                group: Group::G2,
+                index: 0,
+                error,
+            })?;
+        let g1_error = |error| DeserialiseError::InvalidPoint {
+            group: Group::G1,
+            index: 0,
+            error,
+        };
+        let new_accumulated_point = match read_u8(reader)? {
+            0 => None,
+            1 => Some(
+                g1_from_reader(reader, encoding)
+                    .map_err(g1_error)?
+                    .into_projective(),
+            ),
+            _ => return Err(g1_error(PointDeserialiseError::InvalidFlags)),
+        };
+
+        Ok(Checkpoint {
+            public_key,
+            num_g1_powers,
+            num_g2_powers,
+            encoding,
+            points_done,
+            new_accumulated_point,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::PrivateKey;
+
+    #[test]
+    fn srs_binary_roundtrip() {
+        let mut acc = SRS::new(Parameters::new(100, 25)).unwrap();
+        acc.update(PrivateKey::from_u64(5687));
+
+        for encoding in [PointEncoding::Compressed, PointEncoding::Uncompressed] {
+            let mut bytes = Vec::new();
+            acc.write_to(&mut bytes, encoding).unwrap();
+
+            let point_sizes = match encoding {
+                PointEncoding::Compressed => (48, 96),
+                PointEncoding::Uncompressed => (96, 192),
+            };
+            // magic + version + num powers + encoding flag
+            let header_size = 4 + 1 + 8 + 8 + 1;
+            assert_eq!(
+                bytes.len(),
+                header_size + 100 * point_sizes.0 + 25 * point_sizes.1
+            );
+
+            let deserialised_srs = SRS::read_from(&mut bytes.as_slice()).unwrap();
+            assert_eq!(acc, deserialised_srs);
+        }
+    }
+
+    #[test]
+    fn update_proof_binary_roundtrip() {
+        let mut acc = SRS::new(Parameters::new(10, 2)).unwrap();
+        let proof = acc.update(PrivateKey::from_u64(789));
+
+        for encoding in [PointEncoding::Compressed, PointEncoding::Uncompressed] {
+            let mut bytes = Vec::new();
+            proof.write_to(&mut bytes, encoding).unwrap();
+            let deserialised_proof = UpdateProof::read_from(&mut bytes.as_slice()).unwrap();
+            assert_eq!(proof, deserialised_proof);
+        }
+    }
+
+    #[test]
+    fn srs_binary_errors() {
+        let acc = SRS::new(Parameters::new(10, 2)).unwrap();
+        let mut bytes = Vec::new();
+        acc.write_to(&mut bytes, PointEncoding::Compressed).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            SRS::read_from(&mut bad_magic.as_slice()),
+            Err(DeserialiseError::InvalidMagic)
+        );
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 2;
+        assert_eq!(
+            SRS::read_from(&mut bad_version.as_slice()),
+            Err(DeserialiseError::UnsupportedVersion(2))
+        );
+
+        let mut bad_encoding = bytes.clone();
+        bad_encoding[21] = 7;
+        assert_eq!(
+            SRS::read_from(&mut bad_encoding.as_slice()),
+            Err(DeserialiseError::InvalidEncodingFlag(7))
+        );
+
+        // Truncate the last G2 point
+        let truncated = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            SRS::read_from(&mut &truncated[..]),
+            Err(DeserialiseError::InvalidPoint {
+                group: Group::G2,
+                index: 1,
+                error: PointDeserialiseError::Io(std::io::ErrorKind::UnexpectedEof)
+            })
+        );
+    }
+}
@@ -0,0 +1,101 @@
+// A ceremony is the list of update proofs that transition a starting SRS to a final SRS
+//
+// This is used by the Ceremony Integrity Verifier, whom wants to check that the
+// ceremony was carried out correctly and that their contribution was included.
+
+use crate::{error::VerificationError, keypair::PublicKey, srs::SRS, update_proof::UpdateProof};
+use ark_bls12_381::Fr;
+
+pub struct Ceremony;
+
+impl Ceremony {
+    // Verify that the update proofs correspond to the transition from the
+    // starting SRS to the final SRS
+    pub fn verify(
+        starting_srs: &SRS,
+        final_srs: &SRS,
+        update_proofs: &[UpdateProof],
+        random_element: Fr,
+    ) -> Result<(), VerificationError> {
+        SRS::verify_updates(starting_srs, final_srs, update_proofs, random_element)
+    }
+
+    // Verify the ceremony and find the position(s) of a contributors public key in the chain of update proofs
+    //
+    // If the returned list is empty, then the contribution was not included.
+    // A contributor can appear more than once, if they contributed more than once.
+    pub fn verify_and_find_contribution(
+        starting_srs: &SRS,
+        final_srs: &SRS,
+        update_proofs: &[UpdateProof],
+        public_key: PublicKey,
+        random_element: Fr,
+    ) -> (Result<(), VerificationError>, Vec<usize>) {
+        let verified = Ceremony::verify(starting_srs, final_srs, update_proofs, random_element);
+        let positions = Ceremony::find_contribution(update_proofs, public_key);
+        (verified, positions)
+    }
+
+    // Returns the positions of the public key in the chain of update proofs
+    pub fn find_contribution(update_proofs: &[UpdateProof], public_key: PublicKey) -> Vec<usize> {
+        update_proofs
+            .iter()
+            .enumerate()
+            .filter(|(_, update_proof)| update_proof.commitment_to_secret == public_key.point())
+            .map(|(position, _)| position)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::PrivateKey, srs::Parameters};
+
+    #[test]
+    fn find_contribution_smoke() {
+        let starting_srs = SRS::new(Parameters::new(100, 2)).unwrap();
+        let mut final_srs = starting_srs.clone();
+
+        let update_proofs = vec![
+            final_srs.update(PrivateKey::from_u64(252)),
+            final_srs.update(PrivateKey::from_u64(512)),
+            final_srs.update(PrivateKey::from_u64(789)),
+        ];
+        let random_element = Fr::from(123456789u64);
+
+        let public_key = PrivateKey::from_u64(512).to_public();
+        let (verified, positions) = Ceremony::verify_and_find_contribution(
+            &starting_srs,
+            &final_srs,
+            &update_proofs,
+            public_key,
+            random_element,
+        );
+        assert!(verified.is_ok());
+        assert_eq!(positions, vec![1]);
+
+        // A public key that did not contribute, will not be found
+        let public_key = PrivateKey::from_u64(1000).to_public();
+        let (verified, positions) = Ceremony::verify_and_find_contribution(
+            &starting_srs,
+            &final_srs,
+            &update_proofs,
+            public_key,
+            random_element,
+        );
+        assert!(verified.is_ok());
+        assert!(positions.is_empty());
+
+        // If the chain is not valid, we still report the position
+        let (verified, positions) = Ceremony::verify_and_find_contribution(
+            &starting_srs,
+            &final_srs,
+            &update_proofs[1..],
+            PrivateKey::from_u64(789).to_public(),
+            random_element,
+        );
+        assert_eq!(verified, Err(VerificationError::BrokenChain { index: 0 }));
+        assert_eq!(positions, vec![1]);
+    }
+}
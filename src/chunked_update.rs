@@ -0,0 +1,588 @@
+// Updates an SRS in the binary format without holding all of it in memory.
+//
+// `SRS::update` needs every point in memory, which is fine for the KZG ceremony but not for
+// SRS's with 2^20 or more powers. Here the points are read in chunks, each chunk is multiplied
+// by the next powers of the secret and written out, and the power for the next chunk is carried
+// over. Peak memory is bounded by the chunk size.
+//
+// A long update can also save a `Checkpoint` after each chunk, and be resumed from it after
+// a crash, with the same result as an uninterrupted update.
+//
+// Wrap files in a `BufReader`/`BufWriter`, as points are read and written one at a time.
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+};
+
+use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::Field;
+use zeroize::Zeroize;
+
+use crate::{
+    binary_serialisation::{
+        read_encoding, read_header, read_num_powers, write_encoding, write_header, write_u64,
+        SRS_MAGIC,
+    },
+    error::{DeserialiseError, Group, PointDeserialiseError, UpdateError},
+    interop_point_encoding::{
+        g1_from_reader, g1_to_writer, g2_from_reader, g2_to_writer, PointEncoding,
+        G1_SERIALISED_SIZE, G1_UNCOMPRESSED_SIZE, G2_SERIALISED_SIZE, G2_UNCOMPRESSED_SIZE,
+    },
+    keypair::{PrivateKey, PublicKey},
+    srs::{multiply_by_powers, SRS},
+    update_proof::UpdateProof,
+};
+
+impl SRS {
+    // Reads an SRS in the binary format from `reader`, updates it with the private key and
+    // writes the updated SRS to `writer`, using the same point encoding.
+    //
+    // At most `chunk_size` points are held in memory at a time. The output and the update proof
+    // are the same as deserialising the SRS, calling `SRS::update` and serialising it.
+    //
+    // Like `read_from`, we check that every point is on the curve, but not that it is in
+    // the prime order subgroup
+    pub fn update_chunked<R: Read, W: Write>(
+        reader: &mut R,
+        writer: &mut W,
+        private_key: PrivateKey,
+        chunk_size: usize,
+    ) -> Result<UpdateProof, UpdateError> {
+        if chunk_size == 0 {
+            return Err(UpdateError::ZeroChunkSize);
+        }
+
+        let mut checkpoint = Checkpoint::start(reader, private_key.to_public())?;
+        checkpoint.write_srs_header(writer).map_err(write_error)?;
+
+        Chunks {
+            chunk_size,
+            secret: &private_key.tau,
+        }
+        .update(reader, writer, &mut checkpoint, |_| Ok(()))
+    }
+
+    // Like `update_chunked`, but progress can be saved and resumed after a crash.
+    //
+    // After each chunk is written, the output is flushed and `save` is called with a checkpoint.
+    // To resume, call this again with the same input, output, private key and the last saved
+    // checkpoint. The points after the checkpoint are overwritten, so it does not matter if the
+    // output has part of a chunk that was written after it.
+    //
+    // The checkpoint does not hold any secret values, so it can be stored anywhere. The power
+    // of the secret for the next chunk is recomputed from its index.
+    pub fn update_checkpointed<R, W, F>(
+        reader: &mut R,
+        writer: &mut W,
+        private_key: PrivateKey,
+        chunk_size: usize,
+        checkpoint: Option<Checkpoint>,
+        save: F,
+    ) -> Result<UpdateProof, UpdateError>
+    where
+        R: Read + Seek,
+        W: Write + Seek,
+        F: FnMut(&Checkpoint) -> std::io::Result<()>,
+    {
+        if chunk_size == 0 {
+            return Err(UpdateError::ZeroChunkSize);
+        }
+
+        reader
+            .seek(SeekFrom::Start(0))
+            .map_err(|error| DeserialiseError::Io(error.kind()))?;
+        let mut started = Checkpoint::start(reader, private_key.to_public())?;
+        let header_size = reader
+            .stream_position()
+            .map_err(|error| DeserialiseError::Io(error.kind()))?;
+
+        match checkpoint {
+            Some(checkpoint) => {
+                if !checkpoint.resumes(&started) {
+                    return Err(UpdateError::CheckpointMismatch);
+                }
+                // The stored point becomes the update proof, so it must match the point that
+                // was written, which is recomputed from the input
+                if let Some(new_accumulated_point) = checkpoint.new_accumulated_point {
+                    let offset = header_size + g1_point_size(checkpoint.encoding);
+                    reader
+                        .seek(SeekFrom::Start(offset))
+                        .map_err(|error| DeserialiseError::Io(error.kind()))?;
+                    let tau_g1_1 =
+                        g1_from_reader(reader, checkpoint.encoding).map_err(|error| {
+                            DeserialiseError::InvalidPoint {
+                                group: Group::G1,
+                                index: 1,
+                                error,
+                            }
+                        })?;
+                    let mut expected = [tau_g1_1.into_projective()];
+                    let mut power = private_key.tau;
+                    multiply_by_powers(&mut expected, &mut power, &private_key.tau);
+                    power.zeroize();
+                    if expected[0] != new_accumulated_point {
+                        return Err(UpdateError::CheckpointMismatch);
+                    }
+                }
+                started = checkpoint;
+
+                let offset = header_size + started.points_done_size();
+                reader
+                    .seek(SeekFrom::Start(offset))
+                    .map_err(|error| DeserialiseError::Io(error.kind()))?;
+                writer.seek(SeekFrom::Start(offset)).map_err(write_error)?;
+            }
+            None => {
+                writer.seek(SeekFrom::Start(0)).map_err(write_error)?;
+                started.write_srs_header(writer).map_err(write_error)?;
+            }
+        }
+
+        Chunks {
+            chunk_size,
+            secret: &private_key.tau,
+        }
+        .update(reader, writer, &mut started, save)
+    }
+}
+
+// The progress of an update of an SRS file.
+//
+// The G1 points are updated first, followed by the G2 points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    // The public key of the secret that the SRS is being updated with
+    pub(crate) public_key: PublicKey,
+    pub(crate) num_g1_powers: usize,
+    pub(crate) num_g2_powers: usize,
+    pub(crate) encoding: PointEncoding,
+    // The number of points that have been updated and written
+    pub(crate) points_done: usize,
+    // The updated degree-1 G1 point, once it has been written
+    pub(crate) new_accumulated_point: Option<G1Projective>,
+}
+
+impl Checkpoint {
+    // Reads the header of the input SRS, before any points have been updated
+    fn start<R: Read>(reader: &mut R, public_key: PublicKey) -> Result<Checkpoint, UpdateError> {
+        read_header(reader, SRS_MAGIC)?;
+        let num_g1_powers = read_num_powers(reader, Group::G1)?;
+        let num_g2_powers = read_num_powers(reader, Group::G2)?;
+        let encoding = read_encoding(reader)?;
+
+        Ok(Checkpoint {
+            public_key,
+            num_g1_powers,
+            num_g2_powers,
+            encoding,
+            points_done: 0,
+            new_accumulated_point: None,
+        })
+    }
+
+    fn write_srs_header<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_header(writer, SRS_MAGIC)?;
+        write_u64(writer, self.num_g1_powers as u64)?;
+        write_u64(writer, self.num_g2_powers as u64)?;
+        write_encoding(writer, self.encoding)
+    }
+
+    // Whether this checkpoint was saved while updating the same SRS with the same secret
+    fn resumes(&self, started: &Checkpoint) -> bool {
+        self.public_key == started.public_key
+            && self.num_g1_powers == started.num_g1_powers
+            && self.num_g2_powers == started.num_g2_powers
+            && self.encoding == started.encoding
+            && self.points_done <= self.num_g1_powers + self.num_g2_powers
+            && self.new_accumulated_point.is_some() == (self.points_done > 1)
+    }
+
+    // The number of bytes taken by the points that have been written
+    fn points_done_size(&self) -> u64 {
+        let g2_size = match self.encoding {
+            PointEncoding::Compressed => G2_SERIALISED_SIZE,
+            PointEncoding::Uncompressed => G2_UNCOMPRESSED_SIZE,
+        };
+        let g1_done = self.points_done.min(self.num_g1_powers);
+        let g2_done = self.points_done - g1_done;
+        g1_done as u64 * g1_point_size(self.encoding) + (g2_done * g2_size) as u64
+    }
+
+    // The number of points that have been updated and written
+    pub fn points_done(&self) -> usize {
+        self.points_done
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.points_done == self.num_g1_powers + self.num_g2_powers
+    }
+}
+
+// The number of bytes taken by a G1 point with the given encoding
+fn g1_point_size(encoding: PointEncoding) -> u64 {
+    match encoding {
+        PointEncoding::Compressed => G1_SERIALISED_SIZE as u64,
+        PointEncoding::Uncompressed => G1_UNCOMPRESSED_SIZE as u64,
+    }
+}
+
+struct Chunks<'a> {
+    chunk_size: usize,
+    secret: &'a Fr,
+}
+
+impl<'a> Chunks<'a> {
+    // Updates the points after the checkpoint, calling `save` after each chunk
+    fn update<R, W, F>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        checkpoint: &mut Checkpoint,
+        mut save: F,
+    ) -> Result<UpdateProof, UpdateError>
+    where
+        R: Read,
+        W: Write,
+        F: FnMut(&Checkpoint) -> std::io::Result<()>,
+    {
+        let num_g1_powers = checkpoint.num_g1_powers;
+        let num_g2_powers = checkpoint.num_g2_powers;
+
+        if checkpoint.points_done < num_g1_powers {
+            let start = checkpoint.points_done;
+            self.update_points::<G1, _, _, _>(
+                reader,
+                writer,
+                checkpoint.encoding,
+                start..num_g1_powers,
+                |chunk_start, chunk| {
+                    if (chunk_start..chunk_start + chunk.len()).contains(&1) {
+                        checkpoint.new_accumulated_point = Some(chunk[1 - chunk_start]);
+                    }
+                    checkpoint.points_done += chunk.len();
+                    save(checkpoint).map_err(|error| UpdateError::Checkpoint(error.kind()))
+                },
+            )?;
+        }
+
+        let start = checkpoint.points_done - num_g1_powers;
+        self.update_points::<G2, _, _, _>(
+            reader,
+            writer,
+            checkpoint.encoding,
+            start..num_g2_powers,
+            |_, chunk| {
+                checkpoint.points_done += chunk.len();
+                save(checkpoint).map_err(|error| UpdateError::Checkpoint(error.kind()))
+            },
+        )?;
+
+        Ok(UpdateProof {
+            commitment_to_secret: checkpoint.public_key.point(),
+            new_accumulated_point: checkpoint
+                .new_accumulated_point
+                .expect("an SRS has at least two G1 powers"),
+        })
+    }
+
+    // Updates the points of a group in `range`, calling `on_chunk` with the index of the first
+    // point of each chunk and the updated points, once they have been written
+    fn update_points<G, R, W, F>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        encoding: PointEncoding,
+        range: Range<usize>,
+        mut on_chunk: F,
+    ) -> Result<(), UpdateError>
+    where
+        G: StreamedGroup,
+        R: Read,
+        W: Write,
+        F: FnMut(usize, &[G::Projective]) -> Result<(), UpdateError>,
+    {
+        let mut chunk = Vec::with_capacity(self.chunk_size.min(range.len()));
+
+        // The point at index i is multiplied by the secret to the power of i,
+        // so the degree-0 point is unchanged
+        let mut next_power = self.secret.pow([range.start as u64]);
+        let mut chunk_start = range.start;
+        while chunk_start < range.end {
+            let chunk_end = std::cmp::min(chunk_start + self.chunk_size, range.end);
+
+            chunk.clear();
+            for index in chunk_start..chunk_end {
+                let point = G::read_point(reader, encoding).map_err(|error| {
+                    DeserialiseError::InvalidPoint {
+                        group: G::GROUP,
+                        index,
+                        error,
+                    }
+                })?;
+                chunk.push(point.into_projective());
+            }
+
+            multiply_by_powers(&mut chunk, &mut next_power, self.secret);
+            for point in G::Projective::batch_normalization_into_affine(&chunk) {
+                G::write_point(writer, &point, encoding).map_err(write_error)?;
+            }
+            writer.flush().map_err(write_error)?;
+
+            on_chunk(chunk_start, &chunk)?;
+            chunk_start = chunk_end;
+        }
+        next_power.zeroize();
+
+        Ok(())
+    }
+}
+
+// Errors from the input are mapped to `UpdateError::Read` where they happen, so only
+// errors from the output use this
+fn write_error(error: std::io::Error) -> UpdateError {
+    UpdateError::Write(error.kind())
+}
+
+// A group whose points can be streamed in the binary format
+trait StreamedGroup {
+    const GROUP: Group;
+    type Projective: ProjectiveCurve<ScalarField = Fr>;
+
+    fn read_point<R: Read>(
+        reader: &mut R,
+        encoding: PointEncoding,
+    ) -> Result<<Self::Projective as ProjectiveCurve>::Affine, PointDeserialiseError>;
+
+    fn write_point<W: Write>(
+        writer: &mut W,
+        point: &<Self::Projective as ProjectiveCurve>::Affine,
+        encoding: PointEncoding,
+    ) -> std::io::Result<()>;
+}
+
+struct G1;
+
+impl StreamedGroup for G1 {
+    const GROUP: Group = Group::G1;
+    type Projective = G1Projective;
+
+    fn read_point<R: Read>(
+        reader: &mut R,
+        encoding: PointEncoding,
+    ) -> Result<G1Affine, PointDeserialiseError> {
+        g1_from_reader(reader, encoding)
+    }
+
+    fn write_point<W: Write>(
+        writer: &mut W,
+        point: &G1Affine,
+        encoding: PointEncoding,
+    ) -> std::io::Result<()> {
+        g1_to_writer(writer, point, encoding)
+    }
+}
+
+struct G2;
+
+impl StreamedGroup for G2 {
+    const GROUP: Group = Group::G2;
+    type Projective = G2Projective;
+
+    fn read_point<R: Read>(
+        reader: &mut R,
+        encoding: PointEncoding,
+    ) -> Result<G2Affine, PointDeserialiseError> {
+        g2_from_reader(reader, encoding)
+    }
+
+    fn write_point<W: Write>(
+        writer: &mut W,
+        point: &G2Affine,
+        encoding: PointEncoding,
+    ) -> std::io::Result<()> {
+        g2_to_writer(writer, point, encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::srs::Parameters;
+
+    #[test]
+    fn update_chunked_matches_update() {
+        let mut srs = SRS::new(Parameters::new(20, 5)).unwrap();
+        srs.update(PrivateKey::from_u64(1111));
+
+        for encoding in [PointEncoding::Compressed, PointEncoding::Uncompressed] {
+            let mut input = Vec::new();
+            srs.write_to(&mut input, encoding).unwrap();
+
+            let mut expected_srs = srs.clone();
+            let expected_proof = expected_srs.update(PrivateKey::from_u64(2222));
+            let mut expected = Vec::new();
+            expected_srs.write_to(&mut expected, encoding).unwrap();
+
+            for chunk_size in [1, 2, 3, 7, 20, 100] {
+                let mut output = Vec::new();
+                let proof = SRS::update_chunked(
+                    &mut input.as_slice(),
+                    &mut output,
+                    PrivateKey::from_u64(2222),
+                    chunk_size,
+                )
+                .unwrap();
+                assert_eq!(proof, expected_proof);
+                assert_eq!(output, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn update_chunked_errors() {
+        let srs = SRS::new(Parameters::new(20, 5)).unwrap();
+        let mut input = Vec::new();
+        srs.write_to(&mut input, PointEncoding::Compressed).unwrap();
+
+        let result = SRS::update_chunked(
+            &mut input.as_slice(),
+            &mut Vec::new(),
+            PrivateKey::from_u64(2222),
+            0,
+        );
+        assert_eq!(result, Err(UpdateError::ZeroChunkSize));
+
+        // Truncate the input in the middle of the G2 points
+        let truncated = &input[..input.len() - 96 - 10];
+        let result = SRS::update_chunked(
+            &mut &truncated[..],
+            &mut Vec::new(),
+            PrivateKey::from_u64(2222),
+            3,
+        );
+        assert_eq!(
+            result,
+            Err(UpdateError::Read(DeserialiseError::InvalidPoint {
+                group: Group::G2,
+                index: 3,
+                error: PointDeserialiseError::Io(std::io::ErrorKind::UnexpectedEof)
+            }))
+        );
+
+        // An output that runs out of space is reported as a write error
+        let mut small_output = [0u8; 100];
+        let result = SRS::update_chunked(
+            &mut input.as_slice(),
+            &mut &mut small_output[..],
+            PrivateKey::from_u64(2222),
+            3,
+        );
+        assert_eq!(
+            result,
+            Err(UpdateError::Write(std::io::ErrorKind::WriteZero))
+        );
+    }
+
+    #[test]
+    fn update_checkpointed_resumes() {
+        use std::io::Cursor;
+
+        let mut srs = SRS::new(Parameters::new(20, 5)).unwrap();
+        srs.update(PrivateKey::from_u64(1111));
+        let mut input = Vec::new();
+        srs.write_to(&mut input, PointEncoding::Compressed).unwrap();
+
+        let mut expected_srs = srs;
+        let expected_proof = expected_srs.update(PrivateKey::from_u64(2222));
+        let mut expected = Vec::new();
+        expected_srs
+            .write_to(&mut expected, PointEncoding::Compressed)
+            .unwrap();
+
+        // Stop at the start of the G1 points, in the middle of them, and in the G2 points.
+        // There are 7 chunks of G1 points, the last has 2 points
+        for (crash_after, points_done) in [(1, 3), (3, 9), (8, 23)] {
+            let mut output = Cursor::new(Vec::new());
+            let mut saved = Vec::new();
+            let mut num_saved = 0;
+            let result = SRS::update_checkpointed(
+                &mut Cursor::new(&input),
+                &mut output,
+                PrivateKey::from_u64(2222),
+                3,
+                None,
+                |checkpoint| {
+                    if num_saved == crash_after {
+                        return Err(std::io::ErrorKind::Interrupted.into());
+                    }
+                    num_saved += 1;
+                    checkpoint.write_to(&mut saved)
+                },
+            );
+            assert_eq!(
+                result,
+                Err(UpdateError::Checkpoint(std::io::ErrorKind::Interrupted))
+            );
+
+            // Read back the last checkpoint that was saved
+            let mut saved = saved.as_slice();
+            let mut checkpoint = Checkpoint::read_from(&mut saved).unwrap();
+            while !saved.is_empty() {
+                checkpoint = Checkpoint::read_from(&mut saved).unwrap();
+            }
+            assert_eq!(checkpoint.points_done(), points_done);
+            assert!(!checkpoint.is_finished());
+
+            // The chunk after the checkpoint was written before the crash, but is not trusted
+            let mut output = output.into_inner();
+            let len = output.len();
+            output[len - 10..].fill(0xff);
+
+            // A different secret cannot resume the update
+            let result = SRS::update_checkpointed(
+                &mut Cursor::new(&input),
+                &mut Cursor::new(output.clone()),
+                PrivateKey::from_u64(3333),
+                3,
+                Some(checkpoint),
+                |_| Ok(()),
+            );
+            assert_eq!(result, Err(UpdateError::CheckpointMismatch));
+
+            // A checkpoint with a different degree-1 point would give an update proof that
+            // does not match the output
+            if let Some(point) = checkpoint.new_accumulated_point {
+                let mut corrupted = checkpoint;
+                corrupted.new_accumulated_point = Some(point.double());
+                let result = SRS::update_checkpointed(
+                    &mut Cursor::new(&input),
+                    &mut Cursor::new(output.clone()),
+                    PrivateKey::from_u64(2222),
+                    3,
+                    Some(corrupted),
+                    |_| Ok(()),
+                );
+                assert_eq!(result, Err(UpdateError::CheckpointMismatch));
+            }
+
+            let mut output = Cursor::new(output);
+            let mut last_checkpoint = None;
+            let proof = SRS::update_checkpointed(
+                &mut Cursor::new(&input),
+                &mut output,
+                PrivateKey::from_u64(2222),
+                3,
+                Some(checkpoint),
+                |checkpoint| {
+                    last_checkpoint = Some(*checkpoint);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            assert_eq!(proof, expected_proof);
+            assert_eq!(output.into_inner(), expected);
+            assert!(last_checkpoint.unwrap().is_finished());
+        }
+    }
+}
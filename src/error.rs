@@ -0,0 +1,288 @@
+use std::fmt;
+
+// The group that a point in the SRS or in an update proof belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Group {
+    G1,
+    G2,
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Group::G1 => write!(f, "G1"),
+            Group::G2 => write!(f, "G2"),
+        }
+    }
+}
+
+// The reason why a transition from one SRS to another was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationError {
+    // No update proofs were supplied, we never accept a transition without a proof
+    NoUpdateProofs,
+    // The last update proof does not end at the degree-1 element of the final SRS
+    FinalPointMismatch,
+    // The update proof at `index` does not follow on from the previous accumulated point
+    BrokenChain {
+        index: usize,
+    },
+    // The degree-1 element of the final SRS is the identity element
+    IdentityElement(Group),
+    // The G1 elements are not successive powers of the same tau
+    G1StructureCheckFailed,
+    // The G2 elements are not successive powers of the same tau
+    G2StructureCheckFailed,
+    // The random element used for the structure check was zero
+    ZeroRandomElement,
+    // The element at `index` is not in the prime order subgroup
+    SubgroupCheckFailed {
+        group: Group,
+        index: usize,
+    },
+    // The witness does not start at the generators
+    InvalidStartingPoint,
+    // The vectors in the witness are empty or do not all have the same length
+    InconsistentWitness,
+    // The identity signature for the update proof at `index` does not verify against its public key
+    InvalidIdentitySignature {
+        index: usize,
+    },
+    // There is not exactly one identity and one signature for each update proof
+    SignatureCountMismatch {
+        update_proofs: usize,
+        identities: usize,
+        signatures: usize,
+    },
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationError::NoUpdateProofs => write!(f, "no update proofs were supplied"),
+            VerificationError::FinalPointMismatch => write!(
+                f,
+                "the last update proof does not end at the degree-1 element of the final SRS"
+            ),
+            VerificationError::BrokenChain { index } => {
+                write!(f, "the chain of update proofs is broken at proof {}", index)
+            }
+            VerificationError::IdentityElement(group) => {
+                write!(f, "the degree-1 {} element is the identity", group)
+            }
+            VerificationError::G1StructureCheckFailed => {
+                write!(f, "the G1 elements are not successive powers of tau")
+            }
+            VerificationError::G2StructureCheckFailed => {
+                write!(f, "the G2 elements are not successive powers of tau")
+            }
+            VerificationError::ZeroRandomElement => write!(f, "the random element is zero"),
+            VerificationError::SubgroupCheckFailed { group, index } => write!(
+                f,
+                "the {} element at index {} is not in the prime order subgroup",
+                group, index
+            ),
+            VerificationError::InvalidStartingPoint => {
+                write!(f, "the witness does not start at the generators")
+            }
+            VerificationError::InconsistentWitness => {
+                write!(f, "the witness vectors are empty or have different lengths")
+            }
+            VerificationError::InvalidIdentitySignature { index } => write!(
+                f,
+                "the identity signature for update proof {} is invalid",
+                index
+            ),
+            VerificationError::SignatureCountMismatch {
+                update_proofs,
+                identities,
+                signatures,
+            } => write!(
+                f,
+                "got {} update proofs, {} identities and {} signatures",
+                update_proofs, identities, signatures
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+// The reason why a single point could not be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointDeserialiseError {
+    // The hex string did not start with `0x`
+    MissingHexPrefix,
+    // The string after the `0x` prefix was not valid hex
+    InvalidHex,
+    // The decoded bytes were not the size of an encoded point
+    InvalidLength { expected: usize, got: usize },
+    // The compression, infinity and sort flags are not a valid combination
+    InvalidFlags,
+    // A coordinate was not less than the field modulus
+    NonCanonicalFieldElement,
+    // There is no point on the curve with the encoded x-coordinate
+    NotOnCurve,
+    // The point is not in the prime order subgroup
+    NotInSubgroup,
+    // The bytes for the point could not be read
+    Io(std::io::ErrorKind),
+}
+
+impl fmt::Display for PointDeserialiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointDeserialiseError::MissingHexPrefix => write!(f, "missing 0x prefix"),
+            PointDeserialiseError::InvalidHex => write!(f, "invalid hex"),
+            PointDeserialiseError::InvalidLength { expected, got } => {
+                write!(f, "expected {} bytes, got {}", expected, got)
+            }
+            PointDeserialiseError::InvalidFlags => write!(f, "invalid flag bits"),
+            PointDeserialiseError::NonCanonicalFieldElement => {
+                write!(f, "non-canonical field element")
+            }
+            PointDeserialiseError::NotOnCurve => write!(f, "x-coordinate is not on the curve"),
+            PointDeserialiseError::NotInSubgroup => write!(f, "not in the prime order subgroup"),
+            PointDeserialiseError::Io(kind) => write!(f, "could not read point: {}", kind),
+        }
+    }
+}
+
+impl std::error::Error for PointDeserialiseError {}
+
+// The reason why an SRS or an update proof could not be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeserialiseError {
+    // The point at `index` amongst the `group` elements could not be decoded
+    InvalidPoint {
+        group: Group,
+        index: usize,
+        error: PointDeserialiseError,
+    },
+    // The number of `group` elements does not match the parameters
+    WrongNumberOfPowers {
+        group: Group,
+        expected: usize,
+        got: usize,
+    },
+    // An SRS needs at least two elements in each group
+    NotEnoughPowers {
+        group: Group,
+        got: usize,
+    },
+    // The binary header did not start with the expected magic bytes
+    InvalidMagic,
+    // The binary format version is not supported
+    UnsupportedVersion(u8),
+    // The binary header has an unknown point encoding flag
+    InvalidEncodingFlag(u8),
+    // The number of ceremonies in a transcript is not what was expected
+    WrongNumberOfCeremonies {
+        expected: usize,
+        got: usize,
+    },
+    // The binary header could not be read
+    Io(std::io::ErrorKind),
+    // A string in the binary format was not valid UTF-8
+    InvalidUtf8,
+    // The vectors in a witness do not all have the same length
+    WitnessLengthMismatch {
+        running_products: usize,
+        pot_pubkeys: usize,
+        bls_signatures: usize,
+    },
+}
+
+impl fmt::Display for DeserialiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserialiseError::InvalidPoint {
+                group,
+                index,
+                error,
+            } => write!(f, "invalid {} point at index {}: {}", group, index, error),
+            DeserialiseError::WrongNumberOfPowers {
+                group,
+                expected,
+                got,
+            } => write!(f, "expected {} {} powers, got {}", expected, group, got),
+            DeserialiseError::NotEnoughPowers { group, got } => {
+                write!(f, "expected at least 2 {} powers, got {}", group, got)
+            }
+            DeserialiseError::InvalidMagic => write!(f, "invalid magic bytes"),
+            DeserialiseError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {}", version)
+            }
+            DeserialiseError::InvalidEncodingFlag(flag) => {
+                write!(f, "invalid point encoding flag {}", flag)
+            }
+            DeserialiseError::WrongNumberOfCeremonies { expected, got } => {
+                write!(f, "expected {} ceremonies, got {}", expected, got)
+            }
+            DeserialiseError::Io(kind) => write!(f, "could not read header: {}", kind),
+            DeserialiseError::InvalidUtf8 => write!(f, "invalid utf-8 string"),
+            DeserialiseError::WitnessLengthMismatch {
+                running_products,
+                pot_pubkeys,
+                bls_signatures,
+            } => write!(
+                f,
+                "witness has {} running products, {} pot pubkeys and {} bls signatures",
+                running_products, pot_pubkeys, bls_signatures
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeserialiseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeserialiseError::InvalidPoint { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// The reason why an SRS file could not be updated, or a checkpointed update could not be resumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateError {
+    // The SRS could not be read from the input
+    Read(DeserialiseError),
+    // The updated SRS could not be written to the output
+    Write(std::io::ErrorKind),
+    // The number of points in a chunk must be at least one
+    ZeroChunkSize,
+    // A checkpoint could not be saved
+    Checkpoint(std::io::ErrorKind),
+    // The checkpoint was saved while updating a different SRS, or with a different secret
+    CheckpointMismatch,
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateError::Read(error) => write!(f, "could not read SRS: {}", error),
+            UpdateError::Write(kind) => write!(f, "could not write SRS: {}", kind),
+            UpdateError::ZeroChunkSize => write!(f, "the chunk size is zero"),
+            UpdateError::Checkpoint(kind) => write!(f, "could not save checkpoint: {}", kind),
+            UpdateError::CheckpointMismatch => {
+                write!(f, "the checkpoint does not match the SRS or the secret")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UpdateError::Read(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<DeserialiseError> for UpdateError {
+    fn from(error: DeserialiseError) -> Self {
+        UpdateError::Read(error)
+    }
+}
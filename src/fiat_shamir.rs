@@ -0,0 +1,82 @@
+// Derives the random element that is used to verify an update, using the Fiat-Shamir heuristic.
+//
+// The structure check only works if the contributor could not predict the random element
+// when they created the SRS. Instead of trusting the verifier to choose it well, we hash
+// everything that the verification depends on, so the check is non-interactive and deterministic.
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    binary_serialisation::write_u64, hash_to_curve::expand_message_xmd,
+    serialisation::PointEncoding, srs::SRS, update_proof::UpdateProof,
+};
+
+// The domain separation tag used when reducing the transcript hash to a scalar
+const RANDOM_ELEMENT_DST: &[u8] = b"SMALL-POWERS-OF-TAU-V01-UPDATE-CHALLENGE";
+
+// The number of bytes that are reduced into the scalar, so that the bias is negligible.
+// This is ceil((ceil(log2(r)) + k) / 8) where k = 128 is the security level
+const SCALAR_LENGTH: usize = 48;
+
+// Hashes the `before` and `after` SRS along with the update proofs which connect them,
+// and reduces the hash to a scalar.
+//
+// The points are hashed in their compressed binary encoding.
+pub fn derive_random_element(before: &SRS, after: &SRS, update_proofs: &[UpdateProof]) -> Fr {
+    let mut hasher = Sha256::new();
+
+    // Writing into the hasher cannot fail
+    before
+        .write_to(&mut hasher, PointEncoding::Compressed)
+        .expect("hashing cannot fail");
+    after
+        .write_to(&mut hasher, PointEncoding::Compressed)
+        .expect("hashing cannot fail");
+    write_u64(&mut hasher, update_proofs.len() as u64).expect("hashing cannot fail");
+    for update_proof in update_proofs {
+        update_proof
+            .write_to(&mut hasher, PointEncoding::Compressed)
+            .expect("hashing cannot fail");
+    }
+
+    let digest = hasher.finalize();
+    let uniform_bytes = expand_message_xmd(&digest, RANDOM_ELEMENT_DST, SCALAR_LENGTH);
+    Fr::from_be_bytes_mod_order(&uniform_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::PrivateKey, srs::Parameters};
+
+    #[test]
+    fn derive_random_element_binds_inputs() {
+        let before = SRS::new(Parameters::new(4, 2)).unwrap();
+        let mut after = before.clone();
+        let update_proof = after.update(PrivateKey::from_u64(42));
+
+        let random_element = derive_random_element(&before, &after, &[update_proof]);
+        assert_eq!(
+            random_element,
+            derive_random_element(&before, &after, &[update_proof])
+        );
+
+        // Changing any of the inputs changes the random element
+        let mut other_after = after.clone();
+        let other_proof = other_after.update(PrivateKey::from_u64(43));
+        assert_ne!(
+            random_element,
+            derive_random_element(&before, &other_after, &[update_proof])
+        );
+        assert_ne!(
+            random_element,
+            derive_random_element(&before, &after, &[other_proof])
+        );
+        assert_ne!(
+            random_element,
+            derive_random_element(&after, &after, &[update_proof])
+        );
+        assert_ne!(random_element, derive_random_element(&before, &after, &[]));
+    }
+}
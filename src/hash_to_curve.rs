@@ -0,0 +1,556 @@
+// Hashing arbitrary bytes to a point on the curve, following RFC 9380.
+//
+// This implements the random oracle suites `BLS12381G1_XMD:SHA-256_SSWU_RO_` and
+// `BLS12381G2_XMD:SHA-256_SSWU_RO_`:
+// - The message is expanded with `expand_message_xmd` using SHA-256
+// - The bytes are reduced into two field elements
+// - Each field element is mapped to a point on an isogenous curve with the simplified SWU map,
+//   and then moved onto the curve using the 11-isogeny for G1 or the 3-isogeny for G2
+// - The two points are added together and the cofactor is cleared
+//
+// The inputs are public, so none of this is constant time.
+use ark_bls12_381::{Fq, Fq2, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::ProjectiveCurve;
+use ark_ff::{field_new, BigInteger, Field, PrimeField, SquareRootField, Zero};
+use sha2::{Digest, Sha256};
+
+// The output and block sizes of SHA-256 in bytes
+const SHA256_OUTPUT_SIZE: usize = 32;
+const SHA256_BLOCK_SIZE: usize = 64;
+
+// The number of bytes that are reduced into each field element.
+// This is ceil((ceil(log2(p)) + k) / 8) where k = 128 is the security level
+const FIELD_ELEMENT_LENGTH: usize = 64;
+
+// Domain separation tags longer than this are hashed down first
+const MAX_DST_LENGTH: usize = 255;
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+// Clearing the cofactor of G1 can be done by multiplying by 1 - x, where x is the curve parameter
+const G1_EFFECTIVE_COFACTOR: u64 = 0xd201000000010001;
+
+// The effective cofactor for G2, from section 8.8.2 of the RFC, as little-endian limbs
+const G2_EFFECTIVE_COFACTOR: [u64; 10] = [
+    0xe8020005aaa95551,
+    0x59894c0adebbf6b4,
+    0xe954cbc06689f6a3,
+    0x2ec0ec69d7477c1a,
+    0x6d82bf015d1212b0,
+    0x329c2f178731db95,
+    0x9986ff031508ffe1,
+    0x88e2a8e9145ad768,
+    0x584c6a0ea91b3528,
+    0x0bc69f08f2ee75b3,
+];
+
+// The curve E': y^2 = x^3 + A'x + B' which is 11-isogenous to G1, and the non-square Z used by SWU
+const G1_ISO_A: Fq = field_new!(Fq, "12190336318893619529228877361869031420615612348429846051986726275283378313155663745811710833465465981901188123677");
+const G1_ISO_B: Fq = field_new!(Fq, "2906670324641927570491258158026293881577086121416628140204402091718288198173574630967936031029026176254968826637280");
+const G1_SWU_Z: Fq = field_new!(Fq, "11");
+
+// The coefficients of the 11-isogeny from E' to G1, starting with the constant term
+const G1_ISO_X_NUM: [Fq; 12] = [
+    field_new!(Fq, "2712959285290305970661081772124144179193819192423276218370281158706191519995889425075952244140278856085036081760695"),
+    field_new!(Fq, "3564859427549639835253027846704205725951033235539816243131874237388832081954622352624080767121604606753339903542203"),
+    field_new!(Fq, "2051387046688339481714726479723076305756384619135044672831882917686431912682625619320120082313093891743187631791280"),
+    field_new!(Fq, "3612713941521031012780325893181011392520079402153354595775735142359240110423346445050803899623018402874731133626465"),
+    field_new!(Fq, "2247053637822768981792833880270996398470828564809439728372634811976089874056583714987807553397615562273407692740057"),
+    field_new!(Fq, "3415427104483187489859740871640064348492611444552862448295571438270821994900526625562705192993481400731539293415811"),
+    field_new!(Fq, "2067521456483432583860405634125513059912765526223015704616050604591207046392807563217109432457129564962571408764292"),
+    field_new!(Fq, "3650721292069012982822225637849018828271936405382082649291891245623305084633066170122780668657208923883092359301262"),
+    field_new!(Fq, "1239271775787030039269460763652455868148971086016832054354147730155061349388626624328773377658494412538595239256855"),
+    field_new!(Fq, "3479374185711034293956731583912244564891370843071137483962415222733470401948838363051960066766720884717833231600798"),
+    field_new!(Fq, "2492756312273161536685660027440158956721981129429869601638362407515627529461742974364729223659746272460004902959995"),
+    field_new!(Fq, "1058488477413994682556770863004536636444795456512795473806825292198091015005841418695586811009326456605062948114985"),
+];
+
+const G1_ISO_X_DEN: [Fq; 11] = [
+    field_new!(Fq, "1353092447850172218905095041059784486169131709710991428415161466575141675351394082965234118340787683181925558786844"),
+    field_new!(Fq, "2822220997908397120956501031591772354860004534930174057793539372552395729721474912921980407622851861692773516917759"),
+    field_new!(Fq, "1717937747208385987946072944131378949849282930538642983149296304709633281382731764122371874602115081850953846504985"),
+    field_new!(Fq, "501624051089734157816582944025690868317536915684467868346388760435016044027032505306995281054569109955275640941784"),
+    field_new!(Fq, "3025903087998593826923738290305187197829899948335370692927241015584233559365859980023579293766193297662657497834014"),
+    field_new!(Fq, "2224140216975189437834161136818943039444741035168992629437640302964164227138031844090123490881551522278632040105125"),
+    field_new!(Fq, "1146414465848284837484508420047674663876992808692209238763293935905506532411661921697047880549716175045414621825594"),
+    field_new!(Fq, "3179090966864399634396993677377903383656908036827452986467581478509513058347781039562481806409014718357094150199902"),
+    field_new!(Fq, "1549317016540628014674302140786462938410429359529923207442151939696344988707002602944342203885692366490121021806145"),
+    field_new!(Fq, "1442797143427491432630626390066422021593505165588630398337491100088557278058060064930663878153124164818522816175370"),
+    field_new!(Fq, "1"),
+];
+
+const G1_ISO_Y_NUM: [Fq; 16] = [
+    field_new!(Fq, "1393399195776646641963150658816615410692049723305861307490980409834842911816308830479576739332720113414154429643571"),
+    field_new!(Fq, "2968610969752762946134106091152102846225411740689724909058016729455736597929366401532929068084731548131227395540630"),
+    field_new!(Fq, "122933100683284845219599644396874530871261396084070222155796123161881094323788483360414289333111221370374027338230"),
+    field_new!(Fq, "303251954782077855462083823228569901064301365507057490567314302006681283228886645653148231378803311079384246777035"),
+    field_new!(Fq, "1353972356724735644398279028378555627591260676383150667237975415318226973994509601413730187583692624416197017403099"),
+    field_new!(Fq, "3443977503653895028417260979421240655844034880950251104724609885224259484262346958661845148165419691583810082940400"),
+    field_new!(Fq, "718493410301850496156792713845282235942975872282052335612908458061560958159410402177452633054233549648465863759602"),
+    field_new!(Fq, "1466864076415884313141727877156167508644960317046160398342634861648153052436926062434809922037623519108138661903145"),
+    field_new!(Fq, "1536886493137106337339531461344158973554574987550750910027365237255347020572858445054025958480906372033954157667719"),
+    field_new!(Fq, "2171468288973248519912068884667133903101171670397991979582205855298465414047741472281361964966463442016062407908400"),
+    field_new!(Fq, "3915937073730221072189646057898966011292434045388986394373682715266664498392389619761133407846638689998746172899634"),
+    field_new!(Fq, "3802409194827407598156407709510350851173404795262202653149767739163117554648574333789388883640862266596657730112910"),
+    field_new!(Fq, "1707589313757812493102695021134258021969283151093981498394095062397393499601961942449581422761005023512037430861560"),
+    field_new!(Fq, "349697005987545415860583335313370109325490073856352967581197273584891698473628451945217286148025358795756956811571"),
+    field_new!(Fq, "885704436476567581377743161796735879083481447641210566405057346859953524538988296201011389016649354976986251207243"),
+    field_new!(Fq, "3370924952219000111210625390420697640496067348723987858345031683392215988129398381698161406651860675722373763741188"),
+];
+
+const G1_ISO_Y_DEN: [Fq; 16] = [
+    field_new!(Fq, "3396434800020507717552209507749485772788165484415495716688989613875369612529138640646200921379825018840894888371137"),
+    field_new!(Fq, "3907278185868397906991868466757978732688957419873771881240086730384895060595583602347317992689443299391009456758845"),
+    field_new!(Fq, "854914566454823955479427412036002165304466268547334760894270240966182605542146252771872707010378658178126128834546"),
+    field_new!(Fq, "3496628876382137961119423566187258795236027183112131017519536056628828830323846696121917502443333849318934945158166"),
+    field_new!(Fq, "1828256966233331991927609917644344011503610008134915752990581590799656305331275863706710232159635159092657073225757"),
+    field_new!(Fq, "1362317127649143894542621413133849052553333099883364300946623208643344298804722863920546222860227051989127113848748"),
+    field_new!(Fq, "3443845896188810583748698342858554856823966611538932245284665132724280883115455093457486044009395063504744802318172"),
+    field_new!(Fq, "3484671274283470572728732863557945897902920439975203610275006103818288159899345245633896492713412187296754791689945"),
+    field_new!(Fq, "3755735109429418587065437067067640634211015783636675372165599470771975919172394156249639331555277748466603540045130"),
+    field_new!(Fq, "3459661102222301807083870307127272890283709299202626530836335779816726101522661683404130556379097384249447658110805"),
+    field_new!(Fq, "742483168411032072323733249644347333168432665415341249073150659015707795549260947228694495111018381111866512337576"),
+    field_new!(Fq, "1662231279858095762833829698537304807741442669992646287950513237989158777254081548205552083108208170765474149568658"),
+    field_new!(Fq, "1668238650112823419388205992952852912407572045257706138925379268508860023191233729074751042562151098884528280913356"),
+    field_new!(Fq, "369162719928976119195087327055926326601627748362769544198813069133429557026740823593067700396825489145575282378487"),
+    field_new!(Fq, "2164195715141237148945939585099633032390257748382945597506236650132835917087090097395995817229686247227784224263055"),
+    field_new!(Fq, "1"),
+];
+
+// The curve E': y^2 = x^3 + A'x + B' which is 3-isogenous to G2, and the non-square Z used by SWU
+const G2_ISO_A: Fq2 = field_new!(Fq2, field_new!(Fq, "0"), field_new!(Fq, "240"));
+const G2_ISO_B: Fq2 = field_new!(Fq2, field_new!(Fq, "1012"), field_new!(Fq, "1012"));
+const G2_SWU_Z: Fq2 = field_new!(Fq2, field_new!(Fq, "-2"), field_new!(Fq, "-1"));
+
+// The coefficients of the 3-isogeny from E' to G2, starting with the constant term
+const G2_ISO_X_NUM: [Fq2; 4] = [
+    field_new!(
+        Fq2,
+        field_new!(Fq, "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542"),
+        field_new!(Fq, "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "0"),
+        field_new!(Fq, "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706522")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706526"),
+        field_new!(Fq, "1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853261")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "3557697382419259905260257622876359250272784728834673675850718343221361467102966990615722337003569479144794908942033"),
+        field_new!(Fq, "0")
+    ),
+];
+
+const G2_ISO_X_DEN: [Fq2; 3] = [
+    field_new!(
+        Fq2,
+        field_new!(Fq, "0"),
+        field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559715")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "12"),
+        field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559775")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "1"),
+        field_new!(Fq, "0")
+    ),
+];
+
+const G2_ISO_Y_NUM: [Fq2; 4] = [
+    field_new!(
+        Fq2,
+        field_new!(Fq, "3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558"),
+        field_new!(Fq, "3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "0"),
+        field_new!(Fq, "889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235518")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706524"),
+        field_new!(Fq, "1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853263")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "2816510427748580758331037284777117739799287910327449993381818688383577828123182200904113516794492504322962636245776"),
+        field_new!(Fq, "0")
+    ),
+];
+
+const G2_ISO_Y_DEN: [Fq2; 4] = [
+    field_new!(
+        Fq2,
+        field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355"),
+        field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "0"),
+        field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559571")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "18"),
+        field_new!(Fq, "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559769")
+    ),
+    field_new!(
+        Fq2,
+        field_new!(Fq, "1"),
+        field_new!(Fq, "0")
+    ),
+];
+
+// Expands `msg` into `len_in_bytes` pseudo-random bytes, bound to the domain separation tag `dst`
+//
+// Panics if more than 255 * 32 bytes are requested
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    let ell = len_in_bytes.div_ceil(SHA256_OUTPUT_SIZE);
+    assert!(
+        ell <= 255,
+        "cannot expand a message to {} bytes",
+        len_in_bytes
+    );
+
+    let hashed_dst;
+    let dst = if dst.len() > MAX_DST_LENGTH {
+        hashed_dst = Sha256::new()
+            .chain_update(OVERSIZE_DST_PREFIX)
+            .chain_update(dst)
+            .finalize();
+        hashed_dst.as_slice()
+    } else {
+        dst
+    };
+    // DST_prime = DST || I2OSP(len(DST), 1)
+    let dst_len = [dst.len() as u8];
+
+    let b_0 = Sha256::new()
+        .chain_update([0u8; SHA256_BLOCK_SIZE])
+        .chain_update(msg)
+        .chain_update((len_in_bytes as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(dst)
+        .chain_update(dst_len)
+        .finalize();
+
+    let mut b_i = Sha256::new()
+        .chain_update(b_0)
+        .chain_update([1u8])
+        .chain_update(dst)
+        .chain_update(dst_len)
+        .finalize();
+
+    let mut uniform_bytes = Vec::with_capacity(ell * SHA256_OUTPUT_SIZE);
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        let mut xored = [0u8; SHA256_OUTPUT_SIZE];
+        for (x, (a, b)) in xored.iter_mut().zip(b_0.iter().zip(b_i.iter())) {
+            *x = a ^ b;
+        }
+        b_i = Sha256::new()
+            .chain_update(xored)
+            .chain_update([i as u8])
+            .chain_update(dst)
+            .chain_update(dst_len)
+            .finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
+// Hashes `msg` to `N` elements of the base field
+fn hash_to_field_fq<const N: usize>(msg: &[u8], dst: &[u8]) -> [Fq; N] {
+    let uniform_bytes = expand_message_xmd(msg, dst, N * FIELD_ELEMENT_LENGTH);
+    let mut elements = [Fq::zero(); N];
+    for (element, bytes) in elements
+        .iter_mut()
+        .zip(uniform_bytes.chunks_exact(FIELD_ELEMENT_LENGTH))
+    {
+        *element = Fq::from_be_bytes_mod_order(bytes);
+    }
+    elements
+}
+
+// Hashes `msg` to two elements of the quadratic extension field
+fn hash_to_field_fq2(msg: &[u8], dst: &[u8]) -> [Fq2; 2] {
+    let [a_0, a_1, b_0, b_1] = hash_to_field_fq::<4>(msg, dst);
+    [Fq2::new(a_0, a_1), Fq2::new(b_0, b_1)]
+}
+
+// The "sign" of a field element, as defined in the RFC
+fn sgn0_fq(element: &Fq) -> bool {
+    element.into_repr().is_odd()
+}
+
+fn sgn0_fq2(element: &Fq2) -> bool {
+    let sign_0 = sgn0_fq(&element.c0);
+    let zero_0 = element.c0.is_zero();
+    let sign_1 = sgn0_fq(&element.c1);
+    sign_0 || (zero_0 && sign_1)
+}
+
+// Evaluates a polynomial whose coefficients start with the constant term
+fn evaluate<F: Field>(coefficients: &[F], x: F) -> F {
+    coefficients
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, coefficient| acc * x + coefficient)
+}
+
+// The parameters of the curve that is isogenous to G1 or G2, and the map from it to the curve
+struct IsogenousCurve<'a, F> {
+    a: F,
+    b: F,
+    z: F,
+    sgn0: fn(&F) -> bool,
+    x_num: &'a [F],
+    x_den: &'a [F],
+    y_num: &'a [F],
+    y_den: &'a [F],
+}
+
+impl<F: SquareRootField> IsogenousCurve<'_, F> {
+    // Maps a field element to a point on the isogenous curve using the simplified SWU map
+    fn map_to_curve_simple_swu(&self, u: F) -> (F, F) {
+        let (a, b, z) = (self.a, self.b, self.z);
+
+        // tv1 = 1 / (Z^2 * u^4 + Z * u^2), with 1/0 = 0
+        let z_u2 = z * u.square();
+        let tv1 = (z_u2.square() + z_u2).inverse().unwrap_or_else(F::zero);
+
+        let x1 = if tv1.is_zero() {
+            b / (z * a)
+        } else {
+            (-b / a) * (F::one() + tv1)
+        };
+        let gx1 = x1.square() * x1 + a * x1 + b;
+
+        let x2 = z_u2 * x1;
+        let gx2 = x2.square() * x2 + a * x2 + b;
+
+        // Exactly one of gx1 and gx2 is a square
+        let (x, mut y) = match gx1.sqrt() {
+            Some(y1) => (x1, y1),
+            None => (x2, gx2.sqrt().expect("gx2 is a square when gx1 is not")),
+        };
+
+        if (self.sgn0)(&u) != (self.sgn0)(&y) {
+            y = -y;
+        }
+        (x, y)
+    }
+
+    // Maps a point on the isogenous curve to the curve.
+    // Returns None when the point is sent to the identity
+    fn iso_map(&self, x: F, y: F) -> Option<(F, F)> {
+        let x_den_inv = evaluate(self.x_den, x).inverse()?;
+        let y_den_inv = evaluate(self.y_den, x).inverse()?;
+
+        let x_mapped = evaluate(self.x_num, x) * x_den_inv;
+        let y_mapped = y * evaluate(self.y_num, x) * y_den_inv;
+        Some((x_mapped, y_mapped))
+    }
+
+    fn map_to_curve(&self, u: F) -> Option<(F, F)> {
+        let (x, y) = self.map_to_curve_simple_swu(u);
+        self.iso_map(x, y)
+    }
+}
+
+const G1_ISOGENOUS_CURVE: IsogenousCurve<'static, Fq> = IsogenousCurve {
+    a: G1_ISO_A,
+    b: G1_ISO_B,
+    z: G1_SWU_Z,
+    sgn0: sgn0_fq,
+    x_num: &G1_ISO_X_NUM,
+    x_den: &G1_ISO_X_DEN,
+    y_num: &G1_ISO_Y_NUM,
+    y_den: &G1_ISO_Y_DEN,
+};
+
+const G2_ISOGENOUS_CURVE: IsogenousCurve<'static, Fq2> = IsogenousCurve {
+    a: G2_ISO_A,
+    b: G2_ISO_B,
+    z: G2_SWU_Z,
+    sgn0: sgn0_fq2,
+    x_num: &G2_ISO_X_NUM,
+    x_den: &G2_ISO_X_DEN,
+    y_num: &G2_ISO_Y_NUM,
+    y_den: &G2_ISO_Y_DEN,
+};
+
+fn map_to_curve_g1(u: Fq) -> G1Projective {
+    match G1_ISOGENOUS_CURVE.map_to_curve(u) {
+        Some((x, y)) => G1Affine::new(x, y, false).into(),
+        None => G1Projective::zero(),
+    }
+}
+
+fn map_to_curve_g2(u: Fq2) -> G2Projective {
+    match G2_ISOGENOUS_CURVE.map_to_curve(u) {
+        Some((x, y)) => G2Affine::new(x, y, false).into(),
+        None => G2Projective::zero(),
+    }
+}
+
+// Hashes `msg` to a point in the prime order subgroup of G1, using the domain separation tag `dst`
+pub fn hash_to_g1(msg: &[u8], dst: &[u8]) -> G1Projective {
+    let [u_0, u_1] = hash_to_field_fq::<2>(msg, dst);
+    let q = map_to_curve_g1(u_0) + map_to_curve_g1(u_1);
+    q.mul([G1_EFFECTIVE_COFACTOR])
+}
+
+// Hashes `msg` to a point in the prime order subgroup of G2, using the domain separation tag `dst`
+pub fn hash_to_g2(msg: &[u8], dst: &[u8]) -> G2Projective {
+    let [u_0, u_1] = hash_to_field_fq2(msg, dst);
+    let q = map_to_curve_g2(u_0) + map_to_curve_g2(u_1);
+    q.mul(G2_EFFECTIVE_COFACTOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interop_point_encoding::{serialize_g1_uncompressed, serialize_g2_uncompressed},
+        interop_subgroup_checks::{g1, g2},
+    };
+
+    const G1_DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+    const G2_DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+    #[test]
+    fn expand_message_xmd_vectors() {
+        // Test vectors from RFC 9380, Appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        let cases: [(&[u8], usize, &str); 3] = [
+            (
+                b"",
+                0x20,
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc",
+                0x20,
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                b"abc",
+                0x80,
+                "abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a\
+                 647e6c3163d40b76a73cf6a5674ef1d890f95b664ee0afa5359a5c4e07985635\
+                 bbecbac65d747d3d2da7ec2b8221b17b0ca9dc8a1ac1c07ea6a1e60583e2cb00\
+                 058e77b7b72a298425cd1b941ad4ec65e8afc50303a22c0f99b0509b4c895f40",
+            ),
+        ];
+
+        for (msg, len_in_bytes, expected) in cases {
+            let got = expand_message_xmd(msg, dst, len_in_bytes);
+            assert_eq!(hex::encode(got), expected);
+        }
+    }
+
+    #[test]
+    fn hash_to_g1_vectors() {
+        // Test vectors from RFC 9380, Appendix J.9.1
+        let cases: [(&[u8], [&str; 2]); 3] = [
+            (
+                b"",
+                [
+                    "052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a1",
+                    "08ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265",
+                ],
+            ),
+            (
+                b"abc",
+                [
+                    "03567bc5ef9c690c2ab2ecdf6a96ef1c139cc0b2f284dca0a9a7943388a49a3aee664ba5379a7655d3c68900be2f6903",
+                    "0b9c15f3fe6e5cf4211f346271d7b01c8f3b28be689c8429c85b67af215533311f0b8dfaaa154fa6b88176c229f2885d",
+                ],
+            ),
+            (
+                b"abcdef0123456789",
+                [
+                    "11e0b079dea29a68f0383ee94fed1b940995272407e3bb916bbf268c263ddd57a6a27200a784cbc248e84f357ce82d98",
+                    "03a87ae2caf14e8ee52e51fa2ed8eefe80f02457004ba4d486d6aa1f517c0889501dc7413753f9599b099ebcbbd2d709",
+                ],
+            ),
+        ];
+
+        for (msg, expected) in cases {
+            let point = hash_to_g1(msg, G1_DST).into_affine();
+            assert_eq!(
+                hex::encode(serialize_g1_uncompressed(&point)),
+                expected.concat()
+            );
+            assert!(g1::is_in_correct_subgroup_assuming_on_curve(&point));
+        }
+    }
+
+    #[test]
+    fn hash_to_g2_vectors() {
+        // Test vectors from RFC 9380, Appendix J.10.1
+        //
+        // The coordinates are given as x.c1, x.c0, y.c1, y.c0 to match the uncompressed encoding
+        let cases: [(&[u8], [&str; 4]); 3] = [
+            (
+                b"",
+                [
+                    "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+                    "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                    "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+                    "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+                ],
+            ),
+            (
+                b"abc",
+                [
+                    "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+                    "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+                    "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+                    "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+                ],
+            ),
+            (
+                b"abcdef0123456789",
+                [
+                    "190d119345b94fbd15497bcba94ecf7db2cbfd1e1fe7da034d26cbba169fb3968288b3fafb265f9ebd380512a71c3f2c",
+                    "121982811d2491fde9ba7ed31ef9ca474f0e1501297f68c298e9f4c0028add35aea8bb83d53c08cfc007c1e005723cd0",
+                    "0bb5e7572275c567462d91807de765611490205a941a5a6af3b1691bfe596c31225d3aabdf15faff860cb4ef17c7c3be",
+                    "05571a0f8d3c08d094576981f4a3b8eda0a8e771fcdcc8ecceaf1356a6acf17574518acb506e435b639353c2e14827c8",
+                ],
+            ),
+        ];
+
+        for (msg, expected) in cases {
+            let point = hash_to_g2(msg, G2_DST).into_affine();
+            assert_eq!(
+                hex::encode(serialize_g2_uncompressed(&point)),
+                expected.concat()
+            );
+            assert!(g2::is_in_correct_subgroup_assuming_on_curve(&point));
+        }
+    }
+
+    #[test]
+    fn hash_to_curve_domain_separation() {
+        let msg = b"small powers of tau";
+        assert_ne!(hash_to_g1(msg, G1_DST), hash_to_g1(msg, b"another dst"));
+        assert_ne!(hash_to_g2(msg, G2_DST), hash_to_g2(msg, b"another dst"));
+
+        // Long domain separation tags are hashed down, and still give points in the subgroup
+        let long_dst = [b'a'; 300];
+        let point = hash_to_g2(msg, &long_dst).into_affine();
+        assert!(g2::is_in_correct_subgroup_assuming_on_curve(&point));
+    }
+}
@@ -1,34 +1,96 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 //  The problem is that the arkworks encoding is not consistent with the "official" encoding for bls12_381
 // So this wrapper code is needed.
 //
 // Code was adapted from zkcrypto/bls12-381
 // This should NOT be audited.
+use crate::error::PointDeserialiseError;
 use ark_bls12_381::{Fq, G1Affine, G2Affine};
 use ark_ff::{BigInteger384, Fp2, PrimeField};
+use serde::{Deserialize, Serialize};
 
 // The compressed size of a serialised G1 element
 pub const G1_SERIALISED_SIZE: usize = 48;
 // The compressed size of a serialised G2 element
 pub const G2_SERIALISED_SIZE: usize = 96;
+// The uncompressed size of a serialised G1 element
+pub const G1_UNCOMPRESSED_SIZE: usize = 96;
+// The uncompressed size of a serialised G2 element
+pub const G2_UNCOMPRESSED_SIZE: usize = 192;
 
-pub fn g1_from_reader<R: Read>(reader: &mut R) -> Option<G1Affine> {
-    let mut point_bytes = [0u8; G1_SERIALISED_SIZE];
+// Whether points are serialised with only their x-coordinate and a sort flag
+// or with both of their coordinates.
+//
+// Uncompressed points are twice the size, but do not need a square root to decode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PointEncoding {
+    #[default]
+    Compressed,
+    Uncompressed,
+}
 
-    reader.read_exact(&mut point_bytes).ok()?;
-    match deserialize_g1(point_bytes) {
-        Some(point) => Some(point),
-        None => None,
+pub fn g1_from_reader<R: Read>(
+    reader: &mut R,
+    encoding: PointEncoding,
+) -> Result<G1Affine, PointDeserialiseError> {
+    match encoding {
+        PointEncoding::Compressed => {
+            let mut point_bytes = [0u8; G1_SERIALISED_SIZE];
+            read_point_bytes(reader, &mut point_bytes)?;
+            deserialize_g1(point_bytes)
+        }
+        PointEncoding::Uncompressed => {
+            let mut point_bytes = [0u8; G1_UNCOMPRESSED_SIZE];
+            read_point_bytes(reader, &mut point_bytes)?;
+            deserialize_g1_uncompressed(point_bytes)
+        }
     }
 }
-pub fn g2_from_reader<R: Read>(reader: &mut R) -> Option<G2Affine> {
-    let mut point_bytes = [0u8; G2_SERIALISED_SIZE];
-
-    reader.read_exact(&mut point_bytes).ok()?;
-    match deserialize_g2(point_bytes) {
-        Some(point) => Some(point),
-        None => None,
+pub fn g2_from_reader<R: Read>(
+    reader: &mut R,
+    encoding: PointEncoding,
+) -> Result<G2Affine, PointDeserialiseError> {
+    match encoding {
+        PointEncoding::Compressed => {
+            let mut point_bytes = [0u8; G2_SERIALISED_SIZE];
+            read_point_bytes(reader, &mut point_bytes)?;
+            deserialize_g2(point_bytes)
+        }
+        PointEncoding::Uncompressed => {
+            let mut point_bytes = [0u8; G2_UNCOMPRESSED_SIZE];
+            read_point_bytes(reader, &mut point_bytes)?;
+            deserialize_g2_uncompressed(point_bytes)
+        }
+    }
+}
+fn read_point_bytes<R: Read>(
+    reader: &mut R,
+    point_bytes: &mut [u8],
+) -> Result<(), PointDeserialiseError> {
+    reader
+        .read_exact(point_bytes)
+        .map_err(|err| PointDeserialiseError::Io(err.kind()))
+}
+pub fn g1_to_writer<W: Write>(
+    writer: &mut W,
+    point: &G1Affine,
+    encoding: PointEncoding,
+) -> std::io::Result<()> {
+    match encoding {
+        PointEncoding::Compressed => writer.write_all(&serialize_g1(point)),
+        PointEncoding::Uncompressed => writer.write_all(&serialize_g1_uncompressed(point)),
+    }
+}
+pub fn g2_to_writer<W: Write>(
+    writer: &mut W,
+    point: &G2Affine,
+    encoding: PointEncoding,
+) -> std::io::Result<()> {
+    match encoding {
+        PointEncoding::Compressed => writer.write_all(&serialize_g2(point)),
+        PointEncoding::Uncompressed => writer.write_all(&serialize_g2_uncompressed(point)),
     }
 }
 
@@ -37,13 +99,13 @@ fn serialize_g2_x(p: &G2Affine) -> [u8; G2_SERIALISED_SIZE] {
 
     let c1_bytes = serialise_fq(p.x.c1);
     let c0_bytes = serialise_fq(p.x.c0);
-    (&mut result[0..48]).copy_from_slice(&c1_bytes[..]);
-    (&mut result[48..96]).copy_from_slice(&c0_bytes[..]);
+    result[0..48].copy_from_slice(&c1_bytes[..]);
+    result[48..96].copy_from_slice(&c0_bytes[..]);
 
     result
 }
 fn serialize_g1_x(p: &G1Affine) -> [u8; G1_SERIALISED_SIZE] {
-    return serialise_fq(p.x);
+    serialise_fq(p.x)
 }
 
 fn serialise_fq(field: Fq) -> [u8; G1_SERIALISED_SIZE] {
@@ -77,17 +139,16 @@ fn deserialise_fq(bytes: [u8; G1_SERIALISED_SIZE]) -> Option<Fq> {
     Fq::from_repr(tmp)
 }
 
-pub fn deserialize_g1(bytes: [u8; G1_SERIALISED_SIZE]) -> Option<G1Affine> {
+pub fn deserialize_g1(bytes: [u8; G1_SERIALISED_SIZE]) -> Result<G1Affine, PointDeserialiseError> {
     // Obtain the three flags from the start of the byte sequence
     let flags = EncodingFlags::get_flags(&bytes[..]);
 
     if !flags.is_compressed {
-        return None;
-        // unimplemented!("uncompressed serialisation is not implemented")
+        return Err(PointDeserialiseError::InvalidFlags);
     }
 
     if flags.is_infinity {
-        return Some(G1Affine::default());
+        return deserialize_infinity(&flags, &bytes).map(|_| G1Affine::default());
     }
     // Attempt to obtain the x-coordinate
     let x = {
@@ -97,22 +158,23 @@ pub fn deserialize_g1(bytes: [u8; G1_SERIALISED_SIZE]) -> Option<G1Affine> {
         // Mask away the flag bits
         tmp[0] &= 0b0001_1111;
 
-        deserialise_fq(tmp)?
+        deserialise_fq(tmp).ok_or(PointDeserialiseError::NonCanonicalFieldElement)?
     };
 
     G1Affine::get_point_from_x(x, flags.is_lexographically_largest)
+        .ok_or(PointDeserialiseError::NotOnCurve)
 }
 
-pub fn deserialize_g2(bytes: [u8; G2_SERIALISED_SIZE]) -> Option<G2Affine> {
+pub fn deserialize_g2(bytes: [u8; G2_SERIALISED_SIZE]) -> Result<G2Affine, PointDeserialiseError> {
     // Obtain the three flags from the start of the byte sequence
     let flags = EncodingFlags::get_flags(&bytes);
 
-    if flags.is_infinity {
-        return Some(G2Affine::default());
-    }
     if !flags.is_compressed {
-        return None;
-        // unimplemented!("uncompressed serialisation is not implemented")
+        return Err(PointDeserialiseError::InvalidFlags);
+    }
+
+    if flags.is_infinity {
+        return deserialize_infinity(&flags, &bytes).map(|_| G2Affine::default());
     }
 
     // Attempt to obtain the x-coordinate
@@ -123,18 +185,96 @@ pub fn deserialize_g2(bytes: [u8; G2_SERIALISED_SIZE]) -> Option<G2Affine> {
         // Mask away the flag bits
         tmp[0] &= 0b0001_1111;
 
-        deserialise_fq(tmp)?
+        deserialise_fq(tmp).ok_or(PointDeserialiseError::NonCanonicalFieldElement)?
     };
     let xc0 = {
         let mut tmp = [0; G1_SERIALISED_SIZE];
         tmp.copy_from_slice(&bytes[48..96]);
 
-        deserialise_fq(tmp)?
+        deserialise_fq(tmp).ok_or(PointDeserialiseError::NonCanonicalFieldElement)?
     };
 
     let x = Fp2::new(xc0, xc1);
 
     G2Affine::get_point_from_x(x, flags.is_lexographically_largest)
+        .ok_or(PointDeserialiseError::NotOnCurve)
+}
+
+// Decodes a 48 byte coordinate, masking away the flag bits if the coordinate is
+// at the start of the encoding
+fn deserialise_coordinate(bytes: &[u8], mask_flags: bool) -> Result<Fq, PointDeserialiseError> {
+    let mut tmp = [0; G1_SERIALISED_SIZE];
+    tmp.copy_from_slice(bytes);
+
+    if mask_flags {
+        tmp[0] &= 0b0001_1111;
+    }
+
+    deserialise_fq(tmp).ok_or(PointDeserialiseError::NonCanonicalFieldElement)
+}
+
+pub fn deserialize_g1_uncompressed(
+    bytes: [u8; G1_UNCOMPRESSED_SIZE],
+) -> Result<G1Affine, PointDeserialiseError> {
+    let flags = EncodingFlags::get_flags(&bytes[..]);
+
+    if flags.is_compressed {
+        return Err(PointDeserialiseError::InvalidFlags);
+    }
+    if flags.is_infinity {
+        return deserialize_infinity(&flags, &bytes).map(|_| G1Affine::default());
+    }
+    // The sort flag is only used for compressed points
+    if flags.is_lexographically_largest {
+        return Err(PointDeserialiseError::InvalidFlags);
+    }
+
+    let x = deserialise_coordinate(&bytes[0..48], true)?;
+    let y = deserialise_coordinate(&bytes[48..96], false)?;
+
+    let point = G1Affine::new(x, y, false);
+    if !point.is_on_curve() {
+        return Err(PointDeserialiseError::NotOnCurve);
+    }
+    Ok(point)
+}
+
+pub fn deserialize_g2_uncompressed(
+    bytes: [u8; G2_UNCOMPRESSED_SIZE],
+) -> Result<G2Affine, PointDeserialiseError> {
+    let flags = EncodingFlags::get_flags(&bytes[..]);
+
+    if flags.is_compressed {
+        return Err(PointDeserialiseError::InvalidFlags);
+    }
+    if flags.is_infinity {
+        return deserialize_infinity(&flags, &bytes).map(|_| G2Affine::default());
+    }
+    // The sort flag is only used for compressed points
+    if flags.is_lexographically_largest {
+        return Err(PointDeserialiseError::InvalidFlags);
+    }
+
+    let xc1 = deserialise_coordinate(&bytes[0..48], true)?;
+    let xc0 = deserialise_coordinate(&bytes[48..96], false)?;
+    let yc1 = deserialise_coordinate(&bytes[96..144], false)?;
+    let yc0 = deserialise_coordinate(&bytes[144..192], false)?;
+
+    let point = G2Affine::new(Fp2::new(xc0, xc1), Fp2::new(yc0, yc1), false);
+    if !point.is_on_curve() {
+        return Err(PointDeserialiseError::NotOnCurve);
+    }
+    Ok(point)
+}
+
+// The point at infinity has a single valid encoding; the sort flag
+// is not set and every bit after the flags is zero
+fn deserialize_infinity(flags: &EncodingFlags, bytes: &[u8]) -> Result<(), PointDeserialiseError> {
+    let remaining_bits_are_zero = bytes[0] & 0b0001_1111 == 0 && bytes[1..].iter().all(|b| *b == 0);
+    if flags.is_lexographically_largest || !remaining_bits_are_zero {
+        return Err(PointDeserialiseError::InvalidFlags);
+    }
+    Ok(())
 }
 
 struct EncodingFlags {
@@ -166,7 +306,6 @@ impl EncodingFlags {
 
         if self.is_compressed && !self.is_infinity && self.is_lexographically_largest {
             bytes[0] |= 1 << 5;
-            return;
         }
     }
 }
@@ -193,6 +332,40 @@ pub fn serialize_g2(p: &G2Affine) -> [u8; G2_SERIALISED_SIZE] {
     encoding.encode_flags(&mut result[..]);
     result
 }
+pub fn serialize_g1_uncompressed(p: &G1Affine) -> [u8; G1_UNCOMPRESSED_SIZE] {
+    let mut result = [0u8; G1_UNCOMPRESSED_SIZE];
+    // The point at infinity is encoded with both coordinates set to zero
+    if !p.infinity {
+        result[0..48].copy_from_slice(&serialise_fq(p.x));
+        result[48..96].copy_from_slice(&serialise_fq(p.y));
+    }
+    let encoding = EncodingFlags {
+        is_compressed: false,
+        is_infinity: p.infinity,
+        is_lexographically_largest: false,
+    };
+    encoding.encode_flags(&mut result[..]);
+    result
+}
+
+pub fn serialize_g2_uncompressed(p: &G2Affine) -> [u8; G2_UNCOMPRESSED_SIZE] {
+    let mut result = [0u8; G2_UNCOMPRESSED_SIZE];
+    // The point at infinity is encoded with both coordinates set to zero
+    if !p.infinity {
+        result[0..48].copy_from_slice(&serialise_fq(p.x.c1));
+        result[48..96].copy_from_slice(&serialise_fq(p.x.c0));
+        result[96..144].copy_from_slice(&serialise_fq(p.y.c1));
+        result[144..192].copy_from_slice(&serialise_fq(p.y.c0));
+    }
+    let encoding = EncodingFlags {
+        is_compressed: false,
+        is_infinity: p.infinity,
+        is_lexographically_largest: false,
+    };
+    encoding.encode_flags(&mut result[..]);
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,4 +393,115 @@ mod tests {
         let got = deserialize_g2(serialize_g2(&p2)).unwrap();
         assert_eq!(got, p2);
     }
+    #[test]
+    fn test_correct_uncompressed() {
+        let p = G1Affine::prime_subgroup_generator();
+        assert_eq!(hex::encode(serialize_g1_uncompressed(&p)), "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1");
+
+        let mut infinity = [0u8; G1_UNCOMPRESSED_SIZE];
+        infinity[0] = 0x40;
+        assert_eq!(serialize_g1_uncompressed(&G1Affine::default()), infinity);
+        let mut infinity = [0u8; G2_UNCOMPRESSED_SIZE];
+        infinity[0] = 0x40;
+        assert_eq!(serialize_g2_uncompressed(&G2Affine::default()), infinity);
+    }
+    #[test]
+    fn test_serialize_deserialize_uncompressed() {
+        use ark_bls12_381::Fr;
+        use ark_ff::PrimeField;
+
+        let scalar = Fr::from(123456789u64).into_repr();
+        let points_g1 = [
+            G1Affine::prime_subgroup_generator(),
+            G1Affine::prime_subgroup_generator().mul(scalar).into(),
+            -G1Affine::prime_subgroup_generator(),
+            G1Affine::default(),
+        ];
+        for p in points_g1 {
+            let got = deserialize_g1_uncompressed(serialize_g1_uncompressed(&p)).unwrap();
+            assert_eq!(got, p);
+            // The compressed encoding should decode to the same point
+            assert_eq!(deserialize_g1(serialize_g1(&p)).unwrap(), got);
+        }
+        let points_g2 = [
+            G2Affine::prime_subgroup_generator(),
+            G2Affine::prime_subgroup_generator().mul(scalar).into(),
+            -G2Affine::prime_subgroup_generator(),
+            G2Affine::default(),
+        ];
+        for p in points_g2 {
+            let got = deserialize_g2_uncompressed(serialize_g2_uncompressed(&p)).unwrap();
+            assert_eq!(got, p);
+            assert_eq!(deserialize_g2(serialize_g2(&p)).unwrap(), got);
+        }
+    }
+    #[test]
+    fn test_deserialize_uncompressed_errors() {
+        let p = G1Affine::prime_subgroup_generator();
+
+        // Compression flag is set
+        let mut bytes = serialize_g1_uncompressed(&p);
+        bytes[0] |= 0b1000_0000;
+        assert_eq!(
+            deserialize_g1_uncompressed(bytes),
+            Err(PointDeserialiseError::InvalidFlags)
+        );
+
+        // Sort flag is set
+        let mut bytes = serialize_g1_uncompressed(&p);
+        bytes[0] |= 0b0010_0000;
+        assert_eq!(
+            deserialize_g1_uncompressed(bytes),
+            Err(PointDeserialiseError::InvalidFlags)
+        );
+
+        // The y-coordinate does not match the x-coordinate
+        let mut bytes = serialize_g1_uncompressed(&p);
+        bytes[95] ^= 1;
+        assert_eq!(
+            deserialize_g1_uncompressed(bytes),
+            Err(PointDeserialiseError::NotOnCurve)
+        );
+
+        let mut bytes = serialize_g2_uncompressed(&G2Affine::prime_subgroup_generator());
+        bytes[191] ^= 1;
+        assert_eq!(
+            deserialize_g2_uncompressed(bytes),
+            Err(PointDeserialiseError::NotOnCurve)
+        );
+    }
+    #[test]
+    fn test_deserialize_errors() {
+        // Compression flag is not set
+        let mut bytes = serialize_g1(&G1Affine::prime_subgroup_generator());
+        bytes[0] &= 0b0111_1111;
+        assert_eq!(
+            deserialize_g1(bytes),
+            Err(PointDeserialiseError::InvalidFlags)
+        );
+
+        // Infinity flag is set, but the remaining bits are not zero
+        let mut bytes = serialize_g1(&G1Affine::default());
+        bytes[47] = 1;
+        assert_eq!(
+            deserialize_g1(bytes),
+            Err(PointDeserialiseError::InvalidFlags)
+        );
+
+        // The x-coordinate is larger than the field modulus
+        let mut bytes = [0xffu8; G1_SERIALISED_SIZE];
+        bytes[0] = 0b1001_1111;
+        assert_eq!(
+            deserialize_g1(bytes),
+            Err(PointDeserialiseError::NonCanonicalFieldElement)
+        );
+
+        // x = 0 gives y^2 = 4 in G1, but y^2 = 4(u+1) is not a square in G2
+        let mut bytes = [0u8; G2_SERIALISED_SIZE];
+        bytes[0] = 0b1000_0000;
+        assert_eq!(
+            deserialize_g2(bytes),
+            Err(PointDeserialiseError::NotOnCurve)
+        );
+    }
 }
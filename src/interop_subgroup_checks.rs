@@ -21,7 +21,7 @@ pub mod g1 {
     fn endomorphism(p: &GroupAffine<Parameters>) -> GroupAffine<Parameters> {
         // Endomorphism of the points on the curve.
         // endomorphism_p(x,y) = (BETA * x, y) where BETA is a non-trivial cubic root of unity in Fq.
-        let mut res = (*p).clone();
+        let mut res = *p;
         res.x *= BETA;
         res
     }
@@ -109,10 +109,10 @@ pub mod g2 {
         res.x.frobenius_map(1);
         res.y.frobenius_map(1);
 
-        let tmp_x = res.x.clone();
+        let tmp_x = res.x;
 
-        res.x.c0 = -P_POWER_ENDOMORPHISM_COEFF_0.c1 * &tmp_x.c1;
-        res.x.c1 = P_POWER_ENDOMORPHISM_COEFF_0.c1 * &tmp_x.c0;
+        res.x.c0 = -P_POWER_ENDOMORPHISM_COEFF_0.c1 * tmp_x.c1;
+        res.x.c1 = P_POWER_ENDOMORPHISM_COEFF_0.c1 * tmp_x.c0;
         res.y *= P_POWER_ENDOMORPHISM_COEFF_1;
 
         res
@@ -0,0 +1,155 @@
+// Derives the private key for each ceremony from a bundle of entropy.
+//
+// A contributor should not have to trust any single source of randomness, so the bundle
+// mixes together:
+// - Randomness from the operating system
+// - Any number of strings supplied by the user, for example typed on the keyboard
+// - Optional bytes from a hardware RNG
+//
+// Each source is length-prefixed and hashed with a domain separation tag, and the result
+// is expanded with `expand_message_xmd` into one 48 byte scalar per ceremony, in the same
+// way that `hash_to_field` derives independent field elements.
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use rand_core::{CryptoRng, OsRng, RngCore};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::{hash_to_curve::expand_message_xmd, keypair::PrivateKey};
+
+// The domain separation tag used when deriving the private keys
+pub const KEY_DERIVATION_DST: &[u8] = b"SMALL_POWERS_OF_TAU_KEY_DERIVATION_XMD:SHA-256_V1";
+
+// The number of bytes of OS randomness in a bundle
+const OS_RANDOMNESS_SIZE: usize = 64;
+
+// The number of bytes that are reduced into each private key, so that the bias mod r is negligible
+const KEY_LENGTH: usize = 48;
+
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct EntropyBundle {
+    os_randomness: [u8; OS_RANDOMNESS_SIZE],
+    user_entropy: Vec<Vec<u8>>,
+    hardware_entropy: Option<Vec<u8>>,
+}
+
+impl EntropyBundle {
+    // Creates a bundle using randomness from the operating system
+    pub fn new() -> Self {
+        EntropyBundle::from_rng(OsRng)
+    }
+
+    // Creates a bundle using randomness from the given RNG, in place of the operating system
+    pub fn from_rng<R: RngCore + CryptoRng>(mut rng: R) -> Self {
+        let mut os_randomness = [0u8; OS_RANDOMNESS_SIZE];
+        rng.fill_bytes(&mut os_randomness);
+        EntropyBundle {
+            os_randomness,
+            user_entropy: Vec::new(),
+            hardware_entropy: None,
+        }
+    }
+
+    // Adds a string supplied by the user
+    pub fn add_user_entropy(&mut self, entropy: &[u8]) {
+        self.user_entropy.push(entropy.to_vec());
+    }
+
+    // Sets the bytes read from a hardware RNG
+    pub fn set_hardware_entropy(&mut self, entropy: &[u8]) {
+        if let Some(mut previous) = self.hardware_entropy.replace(entropy.to_vec()) {
+            previous.zeroize();
+        }
+    }
+
+    // Derives an independent private key for each of the `num_ceremonies` ceremonies
+    pub fn derive_private_keys(&self, num_ceremonies: usize) -> Vec<PrivateKey> {
+        let uniform_bytes = Zeroizing::new(expand_message_xmd(
+            &self.encode(),
+            KEY_DERIVATION_DST,
+            KEY_LENGTH * num_ceremonies,
+        ));
+
+        uniform_bytes
+            .chunks_exact(KEY_LENGTH)
+            .map(|bytes| PrivateKey {
+                tau: Fr::from_be_bytes_mod_order(bytes),
+            })
+            .collect()
+    }
+
+    // Encodes every source with a length prefix, so that no two bundles have the same encoding
+    fn encode(&self) -> Zeroizing<Vec<u8>> {
+        let mut encoded = Zeroizing::new(Vec::new());
+        encode_bytes(&mut encoded, &self.os_randomness);
+
+        encoded.extend_from_slice(&(self.user_entropy.len() as u64).to_be_bytes());
+        for entropy in &self.user_entropy {
+            encode_bytes(&mut encoded, entropy);
+        }
+
+        match &self.hardware_entropy {
+            Some(entropy) => {
+                encoded.push(1);
+                encode_bytes(&mut encoded, entropy);
+            }
+            None => encoded.push(0),
+        }
+        encoded
+    }
+}
+
+impl Default for EntropyBundle {
+    fn default() -> Self {
+        EntropyBundle::new()
+    }
+}
+
+fn encode_bytes(encoded: &mut Vec<u8>, bytes: &[u8]) {
+    encoded.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+    encoded.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    fn bundle(seed: u64) -> EntropyBundle {
+        EntropyBundle::from_rng(ChaCha20Rng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn derive_private_keys_mixes_every_source() {
+        let taus = |bundle: &EntropyBundle| -> Vec<Fr> {
+            bundle
+                .derive_private_keys(4)
+                .iter()
+                .map(|private_key| private_key.tau)
+                .collect()
+        };
+
+        let keys = taus(&bundle(1));
+        assert_eq!(keys.len(), 4);
+        assert_eq!(taus(&bundle(1)), keys);
+        // The key for each ceremony is different
+        for (i, key) in keys.iter().enumerate() {
+            assert!(keys[i + 1..].iter().all(|other| other != key));
+        }
+
+        assert_ne!(taus(&bundle(2)), keys);
+
+        let mut with_user = bundle(1);
+        with_user.add_user_entropy(b"hello");
+        assert_ne!(taus(&with_user), keys);
+
+        // Splitting the same bytes differently gives a different key
+        let mut split = bundle(1);
+        split.add_user_entropy(b"hel");
+        split.add_user_entropy(b"lo");
+        assert_ne!(taus(&split), taus(&with_user));
+
+        let mut with_hardware = bundle(1);
+        with_hardware.set_hardware_entropy(&[]);
+        assert_ne!(taus(&with_hardware), keys);
+    }
+}
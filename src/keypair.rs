@@ -1,9 +1,20 @@
-use ark_bls12_381::{Fr, G2Projective};
+use std::fmt;
+
+use ark_bls12_381::{Fr, G1Projective, G2Projective};
 use ark_ec::ProjectiveCurve;
-use ark_ff::{PrimeField, UniformRand};
+use ark_ff::{PrimeField, UniformRand, Zero};
 use rand::Rng;
 use zeroize::ZeroizeOnDrop;
 
+use crate::{
+    hash_to_curve::hash_to_g1,
+    interop_subgroup_checks::{g1, g2},
+    srs::pairing_product_is_one,
+};
+
+// The domain separation tag used when hashing an identity, as specified for the KZG ceremony
+pub const IDENTITY_SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+
 #[derive(ZeroizeOnDrop)]
 pub struct PrivateKey {
     pub(crate) tau: Fr,
@@ -30,9 +41,120 @@ impl PrivateKey {
 }
 
 impl PrivateKey {
-    // Converts a private key into a public key
-    pub fn to_public(self) -> G2Projective {
+    // Computes the public key, which is the commitment to the secret in the update proof
+    pub fn to_public(&self) -> PublicKey {
         let gen_g2 = G2Projective::prime_subgroup_generator();
-        gen_g2.mul(self.tau.into_repr())
+        PublicKey(gen_g2.mul(self.tau.into_repr()))
+    }
+
+    // Signs the identity of the contributor, using the secret they contributed to the SRS.
+    //
+    // This binds the contribution to the identity, since the signature can only be verified
+    // against the public key in the contributors update proof.
+    pub fn sign_identity(&self, identity: &str) -> G1Projective {
+        self.sign_bls_g1(identity.as_bytes(), IDENTITY_SIGNATURE_DST)
+    }
+
+    // Makes a BLS signature in G1 on `message`, hashed to the curve with `dst`.
+    // It is checked with `verify_bls_g1_signature`.
+    pub(crate) fn sign_bls_g1(&self, message: &[u8], dst: &[u8]) -> G1Projective {
+        hash_to_g1(message, dst).mul(self.tau.into_repr())
+    }
+}
+
+// Checks a BLS signature in G1 on `message`, hashed to the curve with `dst`.
+//
+// This checks that e(signature, G2) * e(-H(message), pubkey) == 1
+pub(crate) fn verify_bls_g1_signature(
+    message: &[u8],
+    dst: &[u8],
+    pubkey: G2Projective,
+    signature: G1Projective,
+) -> bool {
+    // An identity signature and key would pass the pairing check for any message
+    if signature.is_zero() || pubkey.is_zero() {
+        return false;
+    }
+    let signature = signature.into_affine();
+    if !g1::is_in_correct_subgroup_assuming_on_curve(&signature) {
+        return false;
+    }
+
+    let hashed_message = hash_to_g1(message, dst);
+    pairing_product_is_one(&[
+        (
+            signature,
+            G2Projective::prime_subgroup_generator().into_affine(),
+        ),
+        ((-hashed_message).into_affine(), pubkey.into_affine()),
+    ])
+}
+
+// The public key of a contributor, also known as the pot pubkey.
+//
+// This is the G2 commitment to the secret in an update proof, so a contributor can use
+// it to find their contribution in a ceremony.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(G2Projective);
+
+impl PublicKey {
+    // Returns None if the point is not in the prime order subgroup
+    pub fn new(point: G2Projective) -> Option<PublicKey> {
+        if !g2::is_in_correct_subgroup_assuming_on_curve(&point.into_affine()) {
+            return None;
+        }
+        Some(PublicKey(point))
+    }
+
+    pub fn point(&self) -> G2Projective {
+        self.0
+    }
+}
+
+impl From<&PrivateKey> for PublicKey {
+    fn from(private_key: &PrivateKey) -> Self {
+        private_key.to_public()
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::PointDeserialiseError, interop_point_encoding::serialize_g2};
+    use ark_bls12_381::{Fq2, G2Affine};
+    use ark_ec::AffineCurve;
+    use ark_ff::One;
+
+    #[test]
+    fn public_key_hex_roundtrip() {
+        let private_key = PrivateKey::from_u64(1234);
+        let public_key = PublicKey::from(&private_key);
+        assert_eq!(public_key, private_key.to_public());
+
+        let hex_str = public_key.to_hex();
+        assert_eq!(hex_str, public_key.to_string());
+        assert_eq!(hex_str.len(), 2 + 2 * 96);
+        assert_eq!(PublicKey::from_hex(&hex_str), Ok(public_key));
+
+        // A point on the curve that is not in the prime order subgroup is rejected
+        let mut x = Fq2::one();
+        let point = loop {
+            if let Some(point) = G2Affine::get_point_from_x(x, true) {
+                break point;
+            }
+            x += Fq2::one();
+        };
+        assert!(PublicKey::new(point.into_projective()).is_none());
+        let hex_str = format!("0x{}", hex::encode(serialize_g2(&point)));
+        assert_eq!(
+            PublicKey::from_hex(&hex_str),
+            Err(PointDeserialiseError::NotInSubgroup)
+        );
     }
 }
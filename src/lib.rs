@@ -1,8 +1,17 @@
+pub mod binary_serialisation;
+pub mod ceremony;
+pub mod chunked_update;
+pub mod error;
+pub mod fiat_shamir;
+pub mod hash_to_curve;
 mod interop_point_encoding;
 mod interop_subgroup_checks;
+pub mod key_derivation;
 pub mod keypair;
 pub mod sdk;
+mod secret_memory;
 pub mod serialisation;
 mod shared_secret;
 pub mod srs;
 pub mod update_proof;
+pub mod witness;
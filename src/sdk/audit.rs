@@ -0,0 +1,336 @@
+// An audit of a whole ceremony, from the initial transcript to the final transcript.
+//
+// Every check is run for every ceremony in the batch, even after one has failed, so that
+// the report lists all of the failures. The report can be serialised to JSON.
+use std::time::Instant;
+
+use ark_bls12_381::{G1Projective, G2Projective};
+use ark_ec::ProjectiveCurve;
+use serde::Serialize;
+
+use crate::{
+    error::{Group, VerificationError},
+    sdk::{config::CeremonyConfig, transcript::Transcript},
+    serialisation::{g2_to_hex_string, PointEncoding},
+    srs::SRS,
+    update_proof::UpdateProof,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditReport {
+    // The name of the ceremony config
+    pub name: String,
+    // Whether every check passed for every ceremony
+    pub valid: bool,
+    // Failures that are not specific to one ceremony, such as a mismatch in the number of ceremonies
+    pub failures: Vec<AuditFailure>,
+    pub ceremonies: Vec<CeremonyReport>,
+    #[serde(rename = "durationMicros")]
+    pub duration_micros: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CeremonyReport {
+    // The index of the ceremony in the batch
+    pub ceremony: usize,
+    #[serde(rename = "numG1Powers")]
+    pub num_g1_powers: usize,
+    #[serde(rename = "numG2Powers")]
+    pub num_g2_powers: usize,
+    pub valid: bool,
+    #[serde(rename = "numContributions")]
+    pub num_contributions: usize,
+    // The pot pubkey of each contribution, in the order that they were applied
+    #[serde(rename = "potPubkeys")]
+    pub pot_pubkeys: Vec<PotPubkeyPosition>,
+    pub failures: Vec<AuditFailure>,
+    #[serde(rename = "durationMicros")]
+    pub duration_micros: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PotPubkeyPosition {
+    pub position: usize,
+    #[serde(rename = "potPubkey")]
+    pub pot_pubkey: String,
+}
+
+// The check that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditCheck {
+    // The transcripts and update proofs do not match the config
+    Config,
+    // `SRS::verify_updates` rejected the transition from the initial to the final SRS
+    Updates,
+    // A point in the final SRS is not in the prime order subgroup
+    SubgroupCheck,
+    // The degree-0 elements of an SRS are not the generators
+    Generators,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditFailure {
+    pub check: AuditCheck,
+    pub error: String,
+    // The transcript that failed the check, "initial" or "final", when only one of them did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
+    // The group and index of the failing point or update proof, when the error has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+}
+
+impl AuditFailure {
+    fn from_verification_error(check: AuditCheck, error: VerificationError) -> Self {
+        let (group, index) = match error {
+            VerificationError::BrokenChain { index }
+            | VerificationError::InvalidIdentitySignature { index } => (None, Some(index)),
+            VerificationError::SubgroupCheckFailed { group, index } => (Some(group), Some(index)),
+            VerificationError::IdentityElement(group) => (Some(group), Some(1)),
+            _ => (None, None),
+        };
+        AuditFailure {
+            check,
+            error: error.to_string(),
+            transcript: None,
+            group: group.map(|group| group.to_string()),
+            index,
+        }
+    }
+}
+
+// Audits the transition from the initial transcript to the final transcript, where
+// `update_proofs[i]` holds every update proof for ceremony i, in the order they were applied.
+//
+// For each ceremony this runs `SRS::verify_updates` with a random element derived by
+// Fiat-Shamir, a subgroup check on the final SRS and checks that the degree-0 elements of
+// both SRSs are the generators.
+pub fn audit_transcripts(
+    config: &CeremonyConfig,
+    initial_transcript: &Transcript,
+    final_transcript: &Transcript,
+    update_proofs: &[Vec<UpdateProof>],
+) -> AuditReport {
+    let start = Instant::now();
+
+    let mut failures = Vec::new();
+    for (name, transcript) in [("initial", initial_transcript), ("final", final_transcript)] {
+        if let Err(error) = config.check_batch(&transcript.batch) {
+            failures.push(AuditFailure {
+                check: AuditCheck::Config,
+                error: format!("the {} transcript: {}", name, error),
+                transcript: Some(name.to_string()),
+                group: None,
+                index: None,
+            });
+        }
+    }
+    if update_proofs.len() != config.ceremonies.len() {
+        failures.push(AuditFailure {
+            check: AuditCheck::Config,
+            error: format!(
+                "expected update proofs for {} ceremonies, got {}",
+                config.ceremonies.len(),
+                update_proofs.len()
+            ),
+            transcript: None,
+            group: None,
+            index: None,
+        });
+    }
+
+    // The ceremonies can only be paired up when the counts match
+    let ceremonies = if failures.is_empty() {
+        initial_transcript
+            .batch
+            .ceremonies()
+            .iter()
+            .zip(final_transcript.batch.ceremonies())
+            .zip(update_proofs)
+            .enumerate()
+            .map(|(ceremony, ((before, after), proofs))| {
+                audit_ceremony(ceremony, before, after, proofs)
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    AuditReport {
+        name: config.name.clone(),
+        valid: failures.is_empty() && ceremonies.iter().all(|report| report.valid),
+        failures,
+        ceremonies,
+        duration_micros: start.elapsed().as_micros() as u64,
+    }
+}
+
+fn audit_ceremony(
+    ceremony: usize,
+    before: &SRS,
+    after: &SRS,
+    update_proofs: &[UpdateProof],
+) -> CeremonyReport {
+    let start = Instant::now();
+    let mut failures = Vec::new();
+
+    if let Err(error) = SRS::verify_updates_fiat_shamir(before, after, update_proofs) {
+        failures.push(AuditFailure::from_verification_error(
+            AuditCheck::Updates,
+            error,
+        ));
+    }
+    if let Err(error) = after.subgroup_check() {
+        failures.push(AuditFailure::from_verification_error(
+            AuditCheck::SubgroupCheck,
+            error,
+        ));
+    }
+    for (name, srs) in [("initial", before), ("final", after)] {
+        if srs.g1_elements()[0] != G1Projective::prime_subgroup_generator() {
+            failures.push(generator_failure(name, Group::G1));
+        }
+        if srs.g2_elements()[0] != G2Projective::prime_subgroup_generator() {
+            failures.push(generator_failure(name, Group::G2));
+        }
+    }
+
+    let pot_pubkeys = update_proofs
+        .iter()
+        .enumerate()
+        .map(|(position, update_proof)| PotPubkeyPosition {
+            position,
+            // A commitment outside of the subgroup is still shown, the update check reports it
+            pot_pubkey: g2_to_hex_string(
+                &update_proof.commitment_to_secret.into_affine(),
+                PointEncoding::Compressed,
+            ),
+        })
+        .collect();
+
+    CeremonyReport {
+        ceremony,
+        num_g1_powers: after.g1_elements().len(),
+        num_g2_powers: after.g2_elements().len(),
+        valid: failures.is_empty(),
+        num_contributions: update_proofs.len(),
+        pot_pubkeys,
+        failures,
+        duration_micros: start.elapsed().as_micros() as u64,
+    }
+}
+
+fn generator_failure(srs_name: &str, group: Group) -> AuditFailure {
+    AuditFailure {
+        check: AuditCheck::Generators,
+        error: format!(
+            "the degree-0 {} element of the {} SRS is not the generator",
+            group, srs_name
+        ),
+        transcript: Some(srs_name.to_string()),
+        group: Some(group.to_string()),
+        index: Some(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keypair::PrivateKey,
+        sdk::{batch::Batch, test_utils::test_config, transcript::update_transcript_with_secrets},
+    };
+
+    #[test]
+    fn audit_report_smoke() {
+        let config = test_config();
+        let initial_transcript = Transcript::with_config(&config).unwrap();
+
+        let mut final_transcript = initial_transcript.clone();
+        let mut update_proofs = vec![Vec::new(), Vec::new()];
+        for secrets in [["0x01", "0x02"], ["0x03", "0x04"]] {
+            let secrets = secrets.iter().map(|secret| secret.to_string()).collect();
+            let (transcript, proofs, _) =
+                update_transcript_with_secrets(&config, final_transcript, secrets).unwrap();
+            final_transcript = transcript;
+            for (ceremony_proofs, proof) in update_proofs.iter_mut().zip(proofs) {
+                ceremony_proofs.push(proof);
+            }
+        }
+
+        let report = audit_transcripts(
+            &config,
+            &initial_transcript,
+            &final_transcript,
+            &update_proofs,
+        );
+        assert!(report.valid);
+        assert_eq!(report.ceremonies.len(), 2);
+        assert_eq!(report.ceremonies[1].num_contributions, 2);
+        assert_eq!(
+            report.ceremonies[1].pot_pubkeys[1].pot_pubkey,
+            PrivateKey::from_u64(4).to_public().to_hex()
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["ceremonies"][0]["numG1Powers"], 8);
+        assert_eq!(json["ceremonies"][0]["potPubkeys"][0]["position"], 0);
+
+        // Swapping the proofs in the second ceremony means the chain no longer ends at the final SRS
+        let mut bad_proofs = update_proofs.clone();
+        bad_proofs[1].swap(0, 1);
+        let report =
+            audit_transcripts(&config, &initial_transcript, &final_transcript, &bad_proofs);
+        assert!(!report.valid);
+        assert!(report.ceremonies[0].valid);
+        assert_eq!(
+            report.ceremonies[1].failures,
+            vec![AuditFailure {
+                check: AuditCheck::Updates,
+                error: VerificationError::FinalPointMismatch.to_string(),
+                transcript: None,
+                group: None,
+                index: None,
+            }]
+        );
+
+        // The first proof of the first ceremony used a secret of one, so it links to the
+        // generator but the next proof does not follow on from it
+        bad_proofs[1].swap(0, 1);
+        bad_proofs[1][0] = bad_proofs[0][0];
+        let report =
+            audit_transcripts(&config, &initial_transcript, &final_transcript, &bad_proofs);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["ceremonies"][1]["failures"][0]["check"], "updates");
+        assert_eq!(json["ceremonies"][1]["failures"][0]["index"], 1);
+
+        // The update proofs must be given for every ceremony
+        let report = audit_transcripts(
+            &config,
+            &initial_transcript,
+            &final_transcript,
+            &update_proofs[..1],
+        );
+        assert!(!report.valid);
+        assert!(report.ceremonies.is_empty());
+        assert_eq!(report.failures[0].check, AuditCheck::Config);
+
+        // A config failure names the transcript that does not match
+        let mut short_transcript = final_transcript.clone();
+        short_transcript.batch =
+            Batch::from_ceremonies(final_transcript.batch.ceremonies()[..1].to_vec());
+        let report = audit_transcripts(
+            &config,
+            &initial_transcript,
+            &short_transcript,
+            &update_proofs,
+        );
+        assert_eq!(report.failures.len(), 1);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["failures"][0]["check"], "config");
+        assert_eq!(json["failures"][0]["transcript"], "final");
+    }
+}
@@ -0,0 +1,310 @@
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    error::VerificationError,
+    keypair::PrivateKey,
+    sdk::{
+        config::CeremonyConfig, BatchDeserialiseError, ConfigError, TranscriptVerificationError,
+    },
+    serialisation::{PointEncoding, SRSJson},
+    srs::{Parameters, SRS},
+    update_proof::UpdateProof,
+};
+
+// A batch of independent ceremonies, which are contributed to at the same time.
+//
+// Each ceremony has its own parameters, so the number of ceremonies and their sizes
+// are chosen at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    ceremonies: Vec<SRS>,
+}
+
+// The ceremonies used by Ethereum for EIP-4844
+impl Default for Batch {
+    fn default() -> Self {
+        Batch::with_config(&CeremonyConfig::default()).expect("the default config is valid")
+    }
+}
+
+impl Batch {
+    // Creates the starting SRS for each ceremony
+    //
+    // Returns None if any of the ceremonies has less than two powers in a group
+    pub fn new(parameters: &[Parameters]) -> Option<Batch> {
+        let ceremonies = parameters
+            .iter()
+            .map(|params| SRS::new(*params))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Batch { ceremonies })
+    }
+
+    // Creates the starting SRS for each ceremony in the config
+    //
+    // The config is validated before any SRS is allocated
+    pub fn with_config(config: &CeremonyConfig) -> Result<Batch, ConfigError> {
+        config.validate()?;
+        Ok(Batch::new(&config.ceremonies).expect("the config was validated"))
+    }
+
+    pub fn from_ceremonies(ceremonies: Vec<SRS>) -> Batch {
+        Batch { ceremonies }
+    }
+
+    pub fn ceremonies(&self) -> &[SRS] {
+        &self.ceremonies
+    }
+
+    // The parameters of each ceremony, taken from the size of its SRS
+    pub fn parameters(&self) -> Vec<Parameters> {
+        self.ceremonies
+            .iter()
+            .map(|srs| Parameters::new(srs.g1_elements().len(), srs.g2_elements().len()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ceremonies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ceremonies.is_empty()
+    }
+
+    // Updates each ceremony with its own private key
+    //
+    // Returns None if there is not exactly one private key for each ceremony
+    pub fn update(&mut self, private_keys: Vec<PrivateKey>) -> Option<Vec<UpdateProof>> {
+        if private_keys.len() != self.len() {
+            return None;
+        }
+
+        let update_proofs = self
+            .ceremonies
+            .iter_mut()
+            .zip(private_keys)
+            .map(|(srs, private_key)| srs.update(private_key))
+            .collect();
+        Some(update_proofs)
+    }
+
+    // Subgroup checks every ceremony, returning the index of the first ceremony that fails
+    // along with the group and index of the point
+    pub fn subgroup_check(&self) -> Result<(), (usize, VerificationError)> {
+        for (ceremony, srs) in self.ceremonies.iter().enumerate() {
+            srs.subgroup_check().map_err(|error| (ceremony, error))?;
+        }
+        Ok(())
+    }
+
+    // Verify that each ceremony in `after` is the result of applying its update proof
+    // to the same ceremony in `before`
+    pub fn verify_update(
+        before: &Batch,
+        after: &Batch,
+        update_proofs: &[UpdateProof],
+        random_elements: &[Fr],
+    ) -> Result<(), TranscriptVerificationError> {
+        Batch::check_sizes(before, after, update_proofs.len())?;
+        if random_elements.len() != before.len() {
+            return Err(TranscriptVerificationError::WrongNumberOfCeremonies {
+                expected: before.len(),
+                got: random_elements.len(),
+            });
+        }
+
+        for (i, (proof, random_element)) in update_proofs.iter().zip(random_elements).enumerate() {
+            SRS::verify_update(
+                &before.ceremonies[i],
+                &after.ceremonies[i],
+                proof,
+                *random_element,
+            )
+            .map_err(|error| TranscriptVerificationError::InvalidUpdate { ceremony: i, error })?;
+        }
+
+        Ok(())
+    }
+
+    // Like `verify_update`, but the random element for each ceremony is derived by hashing
+    // the SRSs and update proof
+    pub fn verify_update_fiat_shamir(
+        before: &Batch,
+        after: &Batch,
+        update_proofs: &[UpdateProof],
+    ) -> Result<(), TranscriptVerificationError> {
+        Batch::check_sizes(before, after, update_proofs.len())?;
+
+        for (i, proof) in update_proofs.iter().enumerate() {
+            SRS::verify_update_fiat_shamir(&before.ceremonies[i], &after.ceremonies[i], proof)
+                .map_err(|error| TranscriptVerificationError::InvalidUpdate {
+                    ceremony: i,
+                    error,
+                })?;
+        }
+
+        Ok(())
+    }
+
+    // Checks that both batches have the same ceremonies, and that there is an update proof for each
+    fn check_sizes(
+        before: &Batch,
+        after: &Batch,
+        num_update_proofs: usize,
+    ) -> Result<(), TranscriptVerificationError> {
+        for got in [after.len(), num_update_proofs] {
+            if got != before.len() {
+                return Err(TranscriptVerificationError::WrongNumberOfCeremonies {
+                    expected: before.len(),
+                    got,
+                });
+            }
+        }
+
+        let before_parameters = before.parameters();
+        let after_parameters = after.parameters();
+        for (ceremony, (before, after)) in
+            before_parameters.iter().zip(&after_parameters).enumerate()
+        {
+            if before != after {
+                return Err(TranscriptVerificationError::ParametersMismatch { ceremony });
+            }
+        }
+
+        Ok(())
+    }
+
+    // Converts each SRS into its JSON form
+    pub fn to_json(&self) -> Vec<SRSJson> {
+        self.to_json_with_encoding(PointEncoding::Compressed)
+    }
+
+    pub fn to_json_with_encoding(&self, encoding: PointEncoding) -> Vec<SRSJson> {
+        self.ceremonies
+            .iter()
+            .map(|srs| SRSJson::with_encoding(srs, encoding))
+            .collect()
+    }
+
+    // Like `from_json`, but the number of ceremonies and the number of powers that each one
+    // claims to have are checked against the config before any point is decoded
+    pub fn from_json_with_config(
+        ceremonies_json: &[SRSJson],
+        config: &CeremonyConfig,
+    ) -> Result<Batch, BatchDeserialiseError> {
+        let parameters: Vec<_> = ceremonies_json.iter().map(SRSJson::parameters).collect();
+        config
+            .check_parameters(&parameters)
+            .map_err(BatchDeserialiseError::ConfigMismatch)?;
+        Batch::from_json(ceremonies_json)
+    }
+
+    // Converts the JSON form of each SRS back into a batch
+    //
+    // The error names the first ceremony that could not be deserialised
+    pub fn from_json(ceremonies_json: &[SRSJson]) -> Result<Batch, BatchDeserialiseError> {
+        let ceremonies = ceremonies_json
+            .iter()
+            .enumerate()
+            .map(|(ceremony, srs_json)| {
+                SRS::try_from(srs_json)
+                    .map_err(|error| BatchDeserialiseError::InvalidSRS { ceremony, error })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Batch { ceremonies })
+    }
+}
+
+// Decodes a 0x prefixed hex string for each ceremony into a private key
+//
+// The hex strings and the decoded bytes are wiped, whether or not they could be decoded
+pub(crate) fn decode_secrets(secrets: &mut [String]) -> Option<Vec<PrivateKey>> {
+    let private_keys = secrets
+        .iter()
+        .map(|secret_hex| {
+            let stripped = secret_hex.strip_prefix("0x")?;
+            let bytes = Zeroizing::new(hex::decode(stripped).ok()?);
+            Some(PrivateKey::from_bytes(&bytes))
+        })
+        .collect();
+    secrets.iter_mut().for_each(Zeroize::zeroize);
+    private_keys
+}
+
+// Decodes a 0x prefixed hex string for each ceremony into a field element
+pub(crate) fn decode_random_elements(
+    random_hex_elements: &[String],
+) -> Result<Vec<Fr>, TranscriptVerificationError> {
+    random_hex_elements
+        .iter()
+        .enumerate()
+        .map(|(ceremony, hex_str)| {
+            let bytes = hex_str
+                .strip_prefix("0x")
+                .and_then(|stripped| hex::decode(stripped).ok())
+                .ok_or(TranscriptVerificationError::InvalidRandomElement { ceremony })?;
+            Ok(Fr::from_be_bytes_mod_order(&bytes))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_update_and_verify() {
+        let parameters = [Parameters::new(8, 2), Parameters::new(4, 3)];
+        let before = Batch::new(&parameters).unwrap();
+        assert_eq!(before.parameters(), parameters);
+
+        let mut after = before.clone();
+        let update_proofs = after
+            .update(vec![PrivateKey::from_u64(10), PrivateKey::from_u64(11)])
+            .unwrap();
+        assert!(after.subgroup_check().is_ok());
+
+        let random_elements = [Fr::from(123u64), Fr::from(456u64)];
+        assert!(Batch::verify_update(&before, &after, &update_proofs, &random_elements).is_ok());
+        assert!(Batch::verify_update_fiat_shamir(&before, &after, &update_proofs).is_ok());
+
+        // Swapping the update proofs breaks both ceremonies, the first is reported
+        let swapped = [update_proofs[1], update_proofs[0]];
+        assert!(matches!(
+            Batch::verify_update(&before, &after, &swapped, &random_elements),
+            Err(TranscriptVerificationError::InvalidUpdate { ceremony: 0, .. })
+        ));
+
+        assert_eq!(
+            Batch::verify_update_fiat_shamir(&before, &after, &update_proofs[..1]),
+            Err(TranscriptVerificationError::WrongNumberOfCeremonies {
+                expected: 2,
+                got: 1
+            })
+        );
+
+        let other = Batch::new(&[Parameters::new(8, 2), Parameters::new(5, 3)]).unwrap();
+        assert_eq!(
+            Batch::verify_update_fiat_shamir(&before, &other, &update_proofs),
+            Err(TranscriptVerificationError::ParametersMismatch { ceremony: 1 })
+        );
+
+        // There must be a private key for each ceremony
+        assert!(after.update(vec![PrivateKey::from_u64(12)]).is_none());
+    }
+
+    #[test]
+    fn decode_secrets_wipes_the_secrets() {
+        let mut secrets = vec!["0x0102".to_string(), "0x03".to_string()];
+        let private_keys = decode_secrets(&mut secrets).unwrap();
+        assert_eq!(private_keys[0].tau, Fr::from(0x0102u64));
+        assert!(secrets.iter().all(String::is_empty));
+
+        // The secrets are wiped even when one of them is invalid
+        let mut secrets = vec!["0x01".to_string(), "02".to_string()];
+        assert!(decode_secrets(&mut secrets).is_none());
+        assert!(secrets.iter().all(String::is_empty));
+    }
+}
@@ -0,0 +1,219 @@
+// The configuration of a batch of ceremonies.
+//
+// This is loaded from a file by the coordinator and the contributors, so that ceremonies
+// with any number and size of SRSs can be run without changing the crate. The sdk update and
+// verify entry points check the config before they touch an SRS. `Batch::with_config`,
+// `Batch::from_json_with_config`, `Transcript::from_json_with_config` and
+// `Transcript::read_with_config` check it before any SRS is allocated or any point is decoded.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Group,
+    sdk::{batch::Batch, ConfigError, TranscriptVerificationError},
+    serialisation::PointEncoding,
+    srs::Parameters,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CeremonyConfig {
+    pub name: String,
+    // The parameters of each ceremony in the batch
+    pub ceremonies: Vec<Parameters>,
+    // The encoding used by `Transcript::write_with_config` and the JSON `with_config` encoders
+    #[serde(default)]
+    pub encoding: PointEncoding,
+    #[serde(default)]
+    pub limits: Limits,
+}
+
+// Upper bounds on the size of a batch, so that a bad config cannot exhaust memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Limits {
+    #[serde(rename = "maxCeremonies")]
+    pub max_ceremonies: usize,
+    #[serde(rename = "maxG1Powers")]
+    pub max_g1_powers: usize,
+    #[serde(rename = "maxG2Powers")]
+    pub max_g2_powers: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_ceremonies: 16,
+            max_g1_powers: 1 << 20,
+            max_g2_powers: 1 << 16,
+        }
+    }
+}
+
+// The ceremonies used by Ethereum for EIP-4844
+impl Default for CeremonyConfig {
+    fn default() -> Self {
+        CeremonyConfig {
+            name: "ethereum-kzg".to_string(),
+            ceremonies: vec![
+                Parameters::new(4096, 65),
+                Parameters::new(8192, 65),
+                Parameters::new(16384, 65),
+                Parameters::new(32768, 65),
+            ],
+            encoding: PointEncoding::Compressed,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl CeremonyConfig {
+    // Checks that there is at least one ceremony, and that every ceremony has at least
+    // two powers in each group and is within the limits
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.ceremonies.is_empty() {
+            return Err(ConfigError::NoCeremonies);
+        }
+        if self.ceremonies.len() > self.limits.max_ceremonies {
+            return Err(ConfigError::TooManyCeremonies {
+                max: self.limits.max_ceremonies,
+                got: self.ceremonies.len(),
+            });
+        }
+
+        for (ceremony, params) in self.ceremonies.iter().enumerate() {
+            let groups = [
+                (
+                    Group::G1,
+                    params.num_g1_elements_needed,
+                    self.limits.max_g1_powers,
+                ),
+                (
+                    Group::G2,
+                    params.num_g2_elements_needed,
+                    self.limits.max_g2_powers,
+                ),
+            ];
+            for (group, got, max) in groups {
+                if got < 2 {
+                    return Err(ConfigError::NotEnoughPowers {
+                        ceremony,
+                        group,
+                        got,
+                    });
+                }
+                if got > max {
+                    return Err(ConfigError::TooManyPowers {
+                        ceremony,
+                        group,
+                        max,
+                        got,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Checks that the config is valid and that the batch has the ceremonies it describes
+    pub fn check_batch(&self, batch: &Batch) -> Result<(), TranscriptVerificationError> {
+        self.check_parameters(&batch.parameters())
+    }
+
+    // Like `check_batch`, but for the parameters of a batch that has not been decoded yet
+    pub fn check_parameters(
+        &self,
+        parameters: &[Parameters],
+    ) -> Result<(), TranscriptVerificationError> {
+        self.validate()
+            .map_err(TranscriptVerificationError::InvalidConfig)?;
+
+        if parameters.len() != self.ceremonies.len() {
+            return Err(TranscriptVerificationError::WrongNumberOfCeremonies {
+                expected: self.ceremonies.len(),
+                got: parameters.len(),
+            });
+        }
+        for (ceremony, (expected, got)) in self.ceremonies.iter().zip(parameters).enumerate() {
+            if expected != got {
+                return Err(TranscriptVerificationError::ParametersMismatch { ceremony });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_json_roundtrip() {
+        let json = r#"{
+            "name": "test",
+            "ceremonies": [
+                { "numG1Powers": 16, "numG2Powers": 2 },
+                { "numG1Powers": 8, "numG2Powers": 4 }
+            ],
+            "encoding": "uncompressed"
+        }"#;
+        let config: CeremonyConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.ceremonies,
+            vec![Parameters::new(16, 2), Parameters::new(8, 4)]
+        );
+        assert_eq!(config.encoding, PointEncoding::Uncompressed);
+        assert_eq!(config.limits, Limits::default());
+        assert!(config.validate().is_ok());
+
+        let roundtrip: CeremonyConfig =
+            serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(roundtrip, config);
+
+        assert!(CeremonyConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn config_validate_errors() {
+        let mut config = CeremonyConfig {
+            name: "test".to_string(),
+            ceremonies: vec![],
+            encoding: PointEncoding::Compressed,
+            limits: Limits {
+                max_ceremonies: 2,
+                max_g1_powers: 100,
+                max_g2_powers: 10,
+            },
+        };
+        assert_eq!(config.validate(), Err(ConfigError::NoCeremonies));
+
+        config.ceremonies = vec![Parameters::new(16, 2); 3];
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::TooManyCeremonies { max: 2, got: 3 })
+        );
+
+        config.ceremonies = vec![Parameters::new(16, 2), Parameters::new(16, 1)];
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::NotEnoughPowers {
+                ceremony: 1,
+                group: Group::G2,
+                got: 1
+            })
+        );
+        assert!(Batch::with_config(&config).is_err());
+
+        config.ceremonies = vec![Parameters::new(1 << 40, 2)];
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::TooManyPowers {
+                ceremony: 0,
+                group: Group::G1,
+                max: 100,
+                got: 1 << 40
+            })
+        );
+        // The config is rejected before the SRS is allocated
+        assert!(Batch::with_config(&config).is_err());
+    }
+}
@@ -1,145 +1,113 @@
-use ark_bls12_381::Fr;
-use ark_ff::PrimeField;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    keypair::PrivateKey,
-    srs::SRS,
+    error::VerificationError,
+    key_derivation::EntropyBundle,
+    keypair::{PrivateKey, PublicKey},
+    sdk::{
+        batch::{decode_random_elements, decode_secrets, Batch},
+        config::CeremonyConfig,
+        BatchDeserialiseError, TranscriptVerificationError,
+    },
+    serialisation::{PointEncoding, SRSJson},
     update_proof::UpdateProof,
-    serialisation::SRSJson,
-    sdk::{NUM_CEREMONIES, CEREMONIES},
 };
 
-pub struct Contribution {
-    pub contributions: [SRS; NUM_CEREMONIES],
+// Updates each ceremony with a private key derived from the entropy bundle
+//
+// Returns None if the contribution does not have the ceremonies in the config
+pub fn update_contribution(
+    config: &CeremonyConfig,
+    contribution: Batch,
+    entropy: &EntropyBundle,
+) -> Option<(Batch, Vec<UpdateProof>, Vec<PublicKey>)> {
+    let private_keys = entropy.derive_private_keys(contribution.len());
+    update_contribution_with_keys(config, contribution, private_keys)
 }
 
-impl Default for Contribution {
-    fn default() -> Self {
-        Contribution {
-            contributions: [
-                SRS::new(CEREMONIES[0]).unwrap(),
-                SRS::new(CEREMONIES[1]).unwrap(),
-                SRS::new(CEREMONIES[2]).unwrap(),
-                SRS::new(CEREMONIES[3]).unwrap(),
-            ],
-        }
-    }
+// Like `update_contribution`, but the caller supplies a 0x prefixed hex secret for each ceremony
+pub fn update_contribution_with_secrets(
+    config: &CeremonyConfig,
+    contribution: Batch,
+    mut secrets: Vec<String>,
+) -> Option<(Batch, Vec<UpdateProof>, Vec<PublicKey>)> {
+    let private_keys = decode_secrets(&mut secrets)?;
+    update_contribution_with_keys(config, contribution, private_keys)
 }
 
-pub fn update_contribution(
-    mut contribution: Contribution,
-    secrets: [String; NUM_CEREMONIES],
-) -> Option<(Contribution, [UpdateProof; NUM_CEREMONIES])> {
-    // Check that the parameters for each SRS is correct
-    for (srs, params) in contribution.contributions.iter().zip(CEREMONIES.into_iter()) {
-        if srs.g1_elements().len() != params.num_g1_elements_needed {
-            return None;
-        }
-        if srs.g2_elements().len() != params.num_g2_elements_needed {
-            return None;
-        }
-    }
-
-    let mut update_proofs = Vec::with_capacity(NUM_CEREMONIES);
-
-    for (i, secret_hex) in secrets.into_iter().enumerate() {
-        if let Some(stripped_point_json) = secret_hex.strip_prefix("0x") {
-            let bytes = hex::decode(stripped_point_json).ok()?;
-            let priv_key = PrivateKey::from_bytes(&bytes);
+fn update_contribution_with_keys(
+    config: &CeremonyConfig,
+    mut contribution: Batch,
+    private_keys: Vec<PrivateKey>,
+) -> Option<(Batch, Vec<UpdateProof>, Vec<PublicKey>)> {
+    config.check_batch(&contribution).ok()?;
+    let public_keys = private_keys.iter().map(PublicKey::from).collect();
+    let update_proofs = contribution.update(private_keys)?;
 
-            let update_proof = contribution.contributions[i].update(priv_key);
-            update_proofs.push(update_proof);
-        } else {
-            return None;
-        }
-    }
-
-    let update_proofs: [UpdateProof; NUM_CEREMONIES] = update_proofs.try_into().unwrap();
-
-    Some((contribution, update_proofs))
+    Some((contribution, update_proofs, public_keys))
 }
 
-pub fn contribution_subgroup_check(contribution: Contribution) -> bool {
-    for srs in &contribution.contributions {
-        if !srs.subgroup_check() {
-            return false;
-        }
-    }
-    true
+pub fn contribution_subgroup_check(contribution: &Batch) -> Result<(), (usize, VerificationError)> {
+    contribution.subgroup_check()
 }
 
 pub fn contribution_verify_update(
-    old_contribution: &Contribution,
-    new_contribution: &Contribution,
-    update_proofs: &[UpdateProof; NUM_CEREMONIES],
-    random_hex_elements: [String; NUM_CEREMONIES],
-) -> bool {
-    for i in 0..NUM_CEREMONIES {
-        // Decode random hex string into a field element
-        //
-        //
-        let hex_str = &random_hex_elements[i];
-        let hex_str = if let Some(stripped_random_hex) = hex_str.strip_prefix("0x") {
-            stripped_random_hex
-        } else {
-            return false;
-        };
-
-        let element = match hex::decode(hex_str) {
-            Ok(bytes) => Fr::from_be_bytes_mod_order(&bytes),
-            Err(_) => return false,
-        };
-
-        // Verify update
-        //
-        let proof = update_proofs[i];
-        let before = &old_contribution.contributions[i];
-        let after = &new_contribution.contributions[i];
-        if !SRS::verify_update(before, after, &proof, element) {
-            return false;
-        };
-    }
+    config: &CeremonyConfig,
+    old_contribution: &Batch,
+    new_contribution: &Batch,
+    update_proofs: &[UpdateProof],
+    random_hex_elements: &[String],
+) -> Result<(), TranscriptVerificationError> {
+    config.check_batch(old_contribution)?;
+    let random_elements = decode_random_elements(random_hex_elements)?;
+    Batch::verify_update(
+        old_contribution,
+        new_contribution,
+        update_proofs,
+        &random_elements,
+    )
+}
 
-    true
+// Like `contribution_verify_update`, but the random element for each ceremony is derived by hashing
+// the contributions and update proofs, so the caller does not need to supply them
+pub fn contribution_verify_update_fiat_shamir(
+    config: &CeremonyConfig,
+    old_contribution: &Batch,
+    new_contribution: &Batch,
+    update_proofs: &[UpdateProof],
+) -> Result<(), TranscriptVerificationError> {
+    config.check_batch(old_contribution)?;
+    Batch::verify_update_fiat_shamir(old_contribution, new_contribution, update_proofs)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ContributionJSON {
-    pub contributions: [SRSJson; NUM_CEREMONIES],
+    pub contributions: Vec<SRSJson>,
 }
 
-impl From<&Contribution> for ContributionJSON {
-    fn from(contribution: &Contribution) -> Self {
-        let contributions_json = contribution
-            .contributions
-            // TODO: can remove clone but will need to try_into for array size
-            .clone()
-            .map(|srs| SRSJson::from(&srs));
+impl ContributionJSON {
+    pub fn with_encoding(contribution: &Batch, encoding: PointEncoding) -> Self {
         Self {
-            contributions: contributions_json,
+            contributions: contribution.to_json_with_encoding(encoding),
         }
     }
+
+    // Encodes the points with the encoding in the config
+    pub fn with_config(contribution: &Batch, config: &CeremonyConfig) -> Self {
+        ContributionJSON::with_encoding(contribution, config.encoding)
+    }
 }
 
-impl From<&ContributionJSON> for Contribution {
-    fn from(contribution_json: &ContributionJSON) -> Self {
-        // TODO: find a cleaner way to write this
-        let contributions_option: [Option<SRS>; NUM_CEREMONIES] = contribution_json
-            .contributions
-            .clone()
-            .map(|srs_json| (&srs_json).into());
+impl From<&Batch> for ContributionJSON {
+    fn from(contribution: &Batch) -> Self {
+        ContributionJSON::with_encoding(contribution, PointEncoding::Compressed)
+    }
+}
 
-        let mut contributions = Vec::new();
+impl TryFrom<&ContributionJSON> for Batch {
+    type Error = BatchDeserialiseError;
 
-        for optional_srs in contributions_option {
-            match optional_srs {
-                Some(srs) => contributions.push(srs),
-                None => return Contribution::default(),
-            }
-        }
-        Self {
-            contributions: contributions.try_into().unwrap(),
-        }
+    fn try_from(contribution_json: &ContributionJSON) -> Result<Self, Self::Error> {
+        Batch::from_json(&contribution_json.contributions)
     }
-}
\ No newline at end of file
+}
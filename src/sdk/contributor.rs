@@ -0,0 +1,181 @@
+// Runs the steps that a contributor takes to update a transcript, in the right order:
+// - Check that the transcript has the ceremonies in the config
+// - Subgroup check the transcript, refusing to contribute if it fails
+// - Derive a private key for each ceremony from the entropy bundle
+// - Update the transcript
+//
+// The contributor keeps a receipt of their pot pubkeys and the digests of the transcript before
+// and after their update, so they can later check that their contribution was included.
+use crate::{
+    key_derivation::EntropyBundle,
+    keypair::PublicKey,
+    sdk::{
+        config::CeremonyConfig,
+        coordinator::ContributionSignatures,
+        transcript::{transcript_subgroup_check, update_transcript, Transcript},
+        transport::Message,
+        ContributorError, TranscriptVerificationError,
+    },
+    update_proof::UpdateProof,
+};
+
+// What the contributor sends to the coordinator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContributionBundle {
+    pub transcript: Transcript,
+    // One update proof for each ceremony in the batch
+    pub update_proofs: Vec<UpdateProof>,
+}
+
+impl ContributionBundle {
+    // The message that submits this contribution to the coordinator, without any signatures
+    pub fn into_message(self, participant_id: String) -> Message {
+        let signatures = ContributionSignatures::unsigned(self.update_proofs.len());
+        Message::SubmitContribution {
+            participant_id,
+            transcript: self.transcript,
+            update_proofs: self.update_proofs,
+            signatures,
+        }
+    }
+}
+
+// What the contributor keeps for themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContributionReceipt {
+    // One pot pubkey for each ceremony in the batch
+    pub pot_pubkeys: Vec<PublicKey>,
+    // The digest of the transcript that was received, and of the transcript that was sent back
+    pub transcript_digest_before: [u8; 32],
+    pub transcript_digest_after: [u8; 32],
+}
+
+pub struct Contributor {
+    config: CeremonyConfig,
+    entropy: EntropyBundle,
+}
+
+impl Contributor {
+    // The entropy bundle is only used for a single contribution, and is wiped when the
+    // contributor is dropped
+    pub fn new(config: CeremonyConfig, entropy: EntropyBundle) -> Self {
+        Contributor { config, entropy }
+    }
+
+    pub fn contribute(
+        self,
+        transcript: Transcript,
+    ) -> Result<(ContributionBundle, ContributionReceipt), ContributorError> {
+        self.config
+            .check_batch(&transcript.batch)
+            .map_err(ContributorError::InvalidTranscript)?;
+        if transcript.witnesses.len() != transcript.batch.len() {
+            return Err(ContributorError::InvalidTranscript(
+                TranscriptVerificationError::WrongNumberOfCeremonies {
+                    expected: transcript.batch.len(),
+                    got: transcript.witnesses.len(),
+                },
+            ));
+        }
+
+        // The contributor must not attest to a transcript with points outside of the subgroup,
+        // so this is checked before any secrets are derived
+        if transcript_subgroup_check(&transcript).is_err() {
+            return Err(ContributorError::SubgroupCheckFailed);
+        }
+
+        let transcript_digest_before = transcript.digest();
+        let (transcript, update_proofs, pot_pubkeys) =
+            update_transcript(&self.config, transcript, &self.entropy)
+                .expect("the transcript was checked against the config");
+        let transcript_digest_after = transcript.digest();
+
+        Ok((
+            ContributionBundle {
+                transcript,
+                update_proofs,
+            },
+            ContributionReceipt {
+                pot_pubkeys,
+                transcript_digest_before,
+                transcript_digest_after,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sdk::{
+            batch::Batch, test_utils::test_config, transcript::transcript_verify_update_fiat_shamir,
+        },
+        srs::{Parameters, SRS},
+    };
+    use ark_bls12_381::{Fq, G1Affine};
+    use ark_ec::AffineCurve;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    fn contributor(seed: u64) -> Contributor {
+        let entropy = EntropyBundle::from_rng(ChaCha20Rng::seed_from_u64(seed));
+        Contributor::new(test_config(), entropy)
+    }
+
+    #[test]
+    fn contributor_workflow() {
+        let config = test_config();
+        let transcript = Transcript::with_config(&config).unwrap();
+
+        let (bundle, receipt) = contributor(1).contribute(transcript.clone()).unwrap();
+        assert!(transcript_verify_update_fiat_shamir(
+            &config,
+            &transcript,
+            &bundle.transcript,
+            &bundle.update_proofs
+        )
+        .is_ok());
+        assert_eq!(receipt.transcript_digest_before, transcript.digest());
+        assert_eq!(receipt.transcript_digest_after, bundle.transcript.digest());
+        assert_ne!(
+            receipt.transcript_digest_before,
+            receipt.transcript_digest_after
+        );
+        for (ceremony, pot_pubkey) in receipt.pot_pubkeys.iter().enumerate() {
+            assert_eq!(
+                pot_pubkey.point(),
+                bundle.transcript.witnesses[ceremony].pot_pubkeys[1]
+            );
+        }
+
+        // The transcript must have the ceremonies in the config
+        let other_transcript = Transcript::new(&[Parameters::new(8, 2)]).unwrap();
+        assert_eq!(
+            contributor(2).contribute(other_transcript),
+            Err(ContributorError::InvalidTranscript(
+                TranscriptVerificationError::WrongNumberOfCeremonies {
+                    expected: 2,
+                    got: 1
+                }
+            ))
+        );
+
+        // A transcript with a G1 point outside of the subgroup is refused
+        let not_in_subgroup = (1u64..)
+            .filter_map(|x| G1Affine::get_point_from_x(Fq::from(x), true))
+            .find(|point| !point.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap()
+            .into_projective();
+        let srs = &transcript.batch.ceremonies()[1];
+        let mut g1s = srs.g1_elements().to_vec();
+        g1s[2] = not_in_subgroup;
+        let bad_srs = SRS::from_vectors(g1s, srs.g2_elements().to_vec()).unwrap();
+        let mut bad_transcript = transcript;
+        bad_transcript.batch =
+            Batch::from_ceremonies(vec![bad_transcript.batch.ceremonies()[0].clone(), bad_srs]);
+        assert_eq!(
+            contributor(3).contribute(bad_transcript),
+            Err(ContributorError::SubgroupCheckFailed)
+        );
+    }
+}
@@ -0,0 +1,482 @@
+// The coordinator runs a ceremony on behalf of the Protocol Verifier.
+//
+// It owns the current transcript and a queue of participants. The participant at the front of
+// the queue is given the transcript and a deadline, and the coordinator accepts their updated
+// transcript only if it arrives before the deadline and `transcript_verify_update` accepts it.
+// Any identity signatures sent with the contribution are checked against the pot pubkeys in
+// the update proofs, and recorded in the witnesses. Each accepted contribution is given an
+// `InclusionReceipt`, signed with the coordinator's key.
+//
+// Time is read from a `Clock`, so that deadlines can be tested without waiting.
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ark_bls12_381::G1Projective;
+use rand_core::{OsRng, RngCore};
+
+use crate::{
+    keypair::PublicKey,
+    sdk::{
+        config::CeremonyConfig,
+        receipt::{CoordinatorKey, InclusionReceipt},
+        transcript::{transcript_verify_update, Transcript},
+        CoordinatorError, TranscriptVerificationError,
+    },
+    update_proof::UpdateProof,
+};
+
+// A source of the current time, as a duration since a fixed point in the past
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+// Reads the time from the operating system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+// A clock that only moves when it is advanced. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    // The clock stops at the largest time that fits in a u64 of nanoseconds
+    pub fn advance(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.nanos
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |now| {
+                Some(now.saturating_add(nanos))
+            })
+            .expect("the update always returns Some");
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+}
+
+// The state handed to the participant whose turn it is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turn {
+    pub participant_id: String,
+    pub transcript: Transcript,
+    // How long the participant has left to submit their contribution. This is relative, since
+    // the coordinator's clock may not be the participant's clock.
+    pub time_remaining: Duration,
+}
+
+// The signatures that a participant may send with their contribution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContributionSignatures {
+    // For each ceremony, an optional BLS signature over the participant id, made with the
+    // secret that was contributed to that ceremony
+    pub bls_signatures: Vec<Option<G1Projective>>,
+    // A signature made with the participant's Ethereum key. The coordinator records it in the
+    // transcript without checking it.
+    pub ecdsa_signature: Option<String>,
+}
+
+impl ContributionSignatures {
+    // No signatures for a batch with the given number of ceremonies
+    pub fn unsigned(num_ceremonies: usize) -> Self {
+        ContributionSignatures {
+            bls_signatures: vec![None; num_ceremonies],
+            ecdsa_signature: None,
+        }
+    }
+}
+
+// A contribution that was accepted by the coordinator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptedContribution {
+    pub participant_id: String,
+    // The index of the contribution in the transcript witnesses and participant ids
+    pub position: usize,
+    // One update proof for each ceremony in the batch
+    pub update_proofs: Vec<UpdateProof>,
+    // The digest of the transcript after the contribution was recorded
+    pub transcript_digest: [u8; 32],
+    pub accepted_at: Duration,
+}
+
+struct ActiveTurn {
+    participant_id: String,
+    deadline: Duration,
+}
+
+pub struct Coordinator<C: Clock> {
+    config: CeremonyConfig,
+    // Signs the receipt for each accepted contribution
+    key: CoordinatorKey,
+    clock: C,
+    // How long a participant has to contribute once it is their turn
+    contribution_timeout: Duration,
+    transcript: Transcript,
+    queue: VecDeque<String>,
+    active: Option<ActiveTurn>,
+    history: Vec<AcceptedContribution>,
+}
+
+impl<C: Clock> Coordinator<C> {
+    // Starts coordinating from the given transcript, which must have the ceremonies in the config
+    pub fn new(
+        config: CeremonyConfig,
+        transcript: Transcript,
+        key: CoordinatorKey,
+        clock: C,
+        contribution_timeout: Duration,
+    ) -> Result<Self, TranscriptVerificationError> {
+        config.check_batch(&transcript.batch)?;
+        Ok(Coordinator {
+            config,
+            key,
+            clock,
+            contribution_timeout,
+            transcript,
+            queue: VecDeque::new(),
+            active: None,
+            history: Vec::new(),
+        })
+    }
+
+    // The key that participants check their receipts against
+    pub fn public_key(&self) -> PublicKey {
+        self.key.public_key()
+    }
+
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    pub fn history(&self) -> &[AcceptedContribution] {
+        &self.history
+    }
+
+    pub fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
+
+    // Adds a participant to the back of the queue
+    //
+    // Returns false if the participant is already queued or it is their turn
+    pub fn join(&mut self, participant_id: String) -> bool {
+        let is_active =
+            matches!(&self.active, Some(active) if active.participant_id == participant_id);
+        if is_active || self.queue.contains(&participant_id) {
+            return false;
+        }
+        self.queue.push_back(participant_id);
+        true
+    }
+
+    // The position of the participant in the queue, where zero is the next participant to
+    // be given a turn
+    pub fn queue_position(&self, participant_id: &str) -> Option<usize> {
+        self.queue
+            .iter()
+            .position(|queued| queued == participant_id)
+    }
+
+    // The turn of the given participant, if it is their turn and their deadline has not passed
+    pub fn current_turn(&mut self, participant_id: &str) -> Option<Turn> {
+        self.expire_active_turn();
+        let active = self.active.as_ref()?;
+        if active.participant_id != participant_id {
+            return None;
+        }
+        Some(self.turn(active))
+    }
+
+    // The participant whose turn it is, if their deadline has not passed
+    pub fn active_participant(&mut self) -> Option<&str> {
+        self.expire_active_turn();
+        self.active
+            .as_ref()
+            .map(|active| active.participant_id.as_str())
+    }
+
+    // Hands the current transcript to the next participant in the queue
+    //
+    // Returns None if the queue is empty, or if another participant still has time to contribute
+    pub fn next_turn(&mut self) -> Option<Turn> {
+        if self.active_participant().is_some() {
+            return None;
+        }
+
+        let participant_id = self.queue.pop_front()?;
+        let active = ActiveTurn {
+            participant_id,
+            deadline: self.clock.now() + self.contribution_timeout,
+        };
+        let turn = self.turn(&active);
+        self.active = Some(active);
+        Some(turn)
+    }
+
+    // Accepts or rejects the contribution of the participant whose turn it is
+    //
+    // Either way, the turn is over. On success the update proofs are recorded in the transcript
+    // witnesses and in the history, and a signed receipt for the contribution is returned.
+    pub fn submit(
+        &mut self,
+        participant_id: &str,
+        new_transcript: Transcript,
+        update_proofs: Vec<UpdateProof>,
+        signatures: ContributionSignatures,
+    ) -> Result<InclusionReceipt, CoordinatorError> {
+        match &self.active {
+            Some(active) if active.participant_id == participant_id => {}
+            _ => return Err(CoordinatorError::NotYourTurn),
+        }
+        let active = self.active.take().expect("checked above");
+        let now = self.clock.now();
+        if now > active.deadline {
+            return Err(CoordinatorError::DeadlineExpired);
+        }
+        let num_ceremonies = self.transcript.batch.len();
+        if signatures.bls_signatures.len() != num_ceremonies {
+            return Err(CoordinatorError::InvalidContribution(
+                TranscriptVerificationError::WrongNumberOfCeremonies {
+                    expected: num_ceremonies,
+                    got: signatures.bls_signatures.len(),
+                },
+            ));
+        }
+
+        // The random elements must not be known to the participant, so they are
+        // chosen after the contribution is received
+        let random_hex_elements = random_hex_elements(self.transcript.batch.len());
+        transcript_verify_update(
+            &self.config,
+            &self.transcript,
+            &new_transcript,
+            &update_proofs,
+            &random_hex_elements,
+        )
+        .map_err(CoordinatorError::InvalidContribution)?;
+
+        // The update proofs have been verified, so each signature is checked against the pot
+        // pubkey that the participant contributed
+        for (ceremony, (update_proof, signature)) in update_proofs
+            .iter()
+            .zip(&signatures.bls_signatures)
+            .enumerate()
+        {
+            if let Some(signature) = signature {
+                if !update_proof.verify_identity_signature(participant_id, *signature) {
+                    return Err(CoordinatorError::InvalidIdentitySignature { ceremony });
+                }
+            }
+        }
+
+        // Only the SRSs are taken from the participant, the history is recorded by the coordinator
+        self.transcript.batch = new_transcript.batch;
+        for ((witness, update_proof), signature) in self
+            .transcript
+            .witnesses
+            .iter_mut()
+            .zip(&update_proofs)
+            .zip(signatures.bls_signatures)
+        {
+            witness.push(update_proof, signature);
+        }
+        self.transcript
+            .participant_ids
+            .push(participant_id.to_string());
+        // As in the spec, a missing ECDSA signature is recorded as an empty string
+        self.transcript
+            .participant_ecdsa_signatures
+            .push(signatures.ecdsa_signature.unwrap_or_default());
+
+        let accepted = AcceptedContribution {
+            participant_id: participant_id.to_string(),
+            position: self.transcript.participant_ids.len() - 1,
+            update_proofs,
+            transcript_digest: self.transcript.digest(),
+            accepted_at: now,
+        };
+        let receipt = InclusionReceipt::sign(&self.key, &accepted);
+        self.history.push(accepted);
+        Ok(receipt)
+    }
+
+    fn turn(&self, active: &ActiveTurn) -> Turn {
+        Turn {
+            participant_id: active.participant_id.clone(),
+            transcript: self.transcript.clone(),
+            time_remaining: active.deadline.saturating_sub(self.clock.now()),
+        }
+    }
+
+    // Ends the turn of the active participant if their deadline has passed
+    fn expire_active_turn(&mut self) {
+        let now = self.clock.now();
+        if matches!(&self.active, Some(active) if now > active.deadline) {
+            self.active = None;
+        }
+    }
+}
+
+// A random 0x prefixed hex string for each ceremony
+fn random_hex_elements(num_ceremonies: usize) -> Vec<String> {
+    (0..num_ceremonies)
+        .map(|_| {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            format!("0x{}", hex::encode(bytes))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keypair::PrivateKey,
+        sdk::{
+            test_utils::{test_config, test_secrets},
+            transcript::update_transcript_with_secrets,
+        },
+    };
+
+    fn unsigned() -> ContributionSignatures {
+        ContributionSignatures::unsigned(test_config().ceremonies.len())
+    }
+
+    fn contribute(config: &CeremonyConfig, turn: &Turn) -> (Transcript, Vec<UpdateProof>) {
+        let (transcript, update_proofs, _) =
+            update_transcript_with_secrets(config, turn.transcript.clone(), test_secrets())
+                .unwrap();
+        (transcript, update_proofs)
+    }
+
+    #[test]
+    fn coordinator_queue_and_deadlines() {
+        let config = test_config();
+        let clock = ManualClock::default();
+        let mut coordinator = Coordinator::new(
+            config.clone(),
+            Transcript::with_config(&config).unwrap(),
+            CoordinatorKey::from_u64(42),
+            clock.clone(),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+
+        assert!(coordinator.next_turn().is_none());
+        assert!(coordinator.join("alice".to_string()));
+        assert!(coordinator.join("bob".to_string()));
+        assert!(coordinator.join("carol".to_string()));
+        assert!(coordinator.join("dave".to_string()));
+        assert!(!coordinator.join("bob".to_string()));
+
+        // Alice contributes in time
+        let turn = coordinator.next_turn().unwrap();
+        assert_eq!(turn.participant_id, "alice");
+        assert_eq!(turn.time_remaining, Duration::from_secs(60));
+        // Bob has to wait for Alice
+        assert!(coordinator.next_turn().is_none());
+        let (transcript, update_proofs) = contribute(&config, &turn);
+        assert_eq!(
+            coordinator.submit("bob", transcript.clone(), update_proofs.clone(), unsigned()),
+            Err(CoordinatorError::NotYourTurn)
+        );
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(
+            coordinator.current_turn("alice").unwrap().time_remaining,
+            Duration::from_secs(30)
+        );
+        // Alice signs her identity in the first ceremony only
+        let signatures = ContributionSignatures {
+            bls_signatures: vec![
+                Some(PrivateKey::from_u64(0x1234).sign_identity("alice")),
+                None,
+            ],
+            ecdsa_signature: Some("0xabcd".to_string()),
+        };
+        let receipt = coordinator
+            .submit(
+                "alice",
+                transcript,
+                update_proofs.clone(),
+                signatures.clone(),
+            )
+            .unwrap();
+        assert!(receipt.verify(&coordinator.public_key()));
+        assert_eq!(receipt.position, 1);
+        assert_eq!(coordinator.transcript().participant_ids[1], "alice");
+        assert_eq!(
+            coordinator.transcript().participant_ecdsa_signatures[1],
+            "0xabcd"
+        );
+        for (witness, signature) in coordinator
+            .transcript()
+            .witnesses
+            .iter()
+            .zip(signatures.bls_signatures)
+        {
+            assert_eq!(witness.bls_signatures[1], signature);
+        }
+        assert_eq!(coordinator.history()[0].update_proofs, update_proofs);
+        assert_eq!(coordinator.history()[0].position, 1);
+        assert_eq!(
+            coordinator.history()[0].transcript_digest,
+            coordinator.transcript().digest()
+        );
+
+        // Bob misses his deadline, so Carol is next
+        let turn = coordinator.next_turn().unwrap();
+        assert_eq!(turn.participant_id, "bob");
+        let (transcript, update_proofs) = contribute(&config, &turn);
+        clock.advance(Duration::from_secs(61));
+        assert_eq!(
+            coordinator.submit("bob", transcript, update_proofs, unsigned()),
+            Err(CoordinatorError::DeadlineExpired)
+        );
+        assert_eq!(coordinator.active_participant(), None);
+
+        // Carol sends back the transcript she was given, which is rejected
+        let turn = coordinator.next_turn().unwrap();
+        assert_eq!(turn.participant_id, "carol");
+        let (_, update_proofs) = contribute(&config, &turn);
+        assert!(matches!(
+            coordinator.submit("carol", turn.transcript, update_proofs, unsigned()),
+            Err(CoordinatorError::InvalidContribution(
+                TranscriptVerificationError::InvalidUpdate { ceremony: 0, .. }
+            ))
+        ));
+        assert_eq!(coordinator.active_participant(), None);
+
+        // Dave signs his identity in the second ceremony with the wrong secret
+        let turn = coordinator.next_turn().unwrap();
+        assert_eq!(turn.participant_id, "dave");
+        let (transcript, update_proofs) = contribute(&config, &turn);
+        let signatures = ContributionSignatures {
+            bls_signatures: vec![None, Some(PrivateKey::from_u64(1).sign_identity("dave"))],
+            ecdsa_signature: None,
+        };
+        assert_eq!(
+            coordinator.submit("dave", transcript, update_proofs, signatures),
+            Err(CoordinatorError::InvalidIdentitySignature { ceremony: 1 })
+        );
+        assert_eq!(coordinator.history().len(), 1);
+        assert_eq!(coordinator.transcript().witnesses[0].len(), 2);
+    }
+}
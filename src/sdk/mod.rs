@@ -1,26 +1,402 @@
-use crate::srs::Parameters;
+use std::fmt;
 
-pub mod transcript;
+use crate::error::{DeserialiseError, Group, PointDeserialiseError, VerificationError};
+
+pub mod audit;
+pub mod batch;
+pub mod config;
 pub mod contribution;
+pub mod contributor;
+pub mod coordinator;
+pub mod receipt;
+pub mod transcript;
+pub mod transport;
+
+// The reason why an update to one of the ceremonies was rejected
+// `ceremony` is the index of the ceremony in the batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptVerificationError {
+    // The random element for the ceremony was not a 0x prefixed hex string
+    InvalidRandomElement {
+        ceremony: usize,
+    },
+    // The update for the ceremony did not verify
+    InvalidUpdate {
+        ceremony: usize,
+        error: VerificationError,
+    },
+    // The SRS for the ceremony does not match the history in its witness
+    InvalidWitness {
+        ceremony: usize,
+        error: VerificationError,
+    },
+    // The number of SRSs, update proofs, random elements or witnesses does not match
+    // the number of ceremonies
+    WrongNumberOfCeremonies {
+        expected: usize,
+        got: usize,
+    },
+    // The SRS for the ceremony does not have the same number of powers as before the update
+    ParametersMismatch {
+        ceremony: usize,
+    },
+    // The ceremony config is not valid
+    InvalidConfig(ConfigError),
+}
+
+impl fmt::Display for TranscriptVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptVerificationError::InvalidRandomElement { ceremony } => {
+                write!(f, "ceremony {}: invalid random element", ceremony)
+            }
+            TranscriptVerificationError::InvalidUpdate { ceremony, error } => {
+                write!(f, "ceremony {}: {}", ceremony, error)
+            }
+            TranscriptVerificationError::InvalidWitness { ceremony, error } => {
+                write!(f, "ceremony {}: invalid witness: {}", ceremony, error)
+            }
+            TranscriptVerificationError::WrongNumberOfCeremonies { expected, got } => {
+                write!(f, "expected {} ceremonies, got {}", expected, got)
+            }
+            TranscriptVerificationError::ParametersMismatch { ceremony } => {
+                write!(f, "ceremony {}: the number of powers has changed", ceremony)
+            }
+            TranscriptVerificationError::InvalidConfig(error) => {
+                write!(f, "invalid ceremony config: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TranscriptVerificationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TranscriptVerificationError::InvalidRandomElement { .. }
+            | TranscriptVerificationError::WrongNumberOfCeremonies { .. }
+            | TranscriptVerificationError::ParametersMismatch { .. } => None,
+            TranscriptVerificationError::InvalidUpdate { error, .. } => Some(error),
+            TranscriptVerificationError::InvalidWitness { error, .. } => Some(error),
+            TranscriptVerificationError::InvalidConfig(error) => Some(error),
+        }
+    }
+}
+
+// The reason why a ceremony config was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    // The config does not have any ceremonies
+    NoCeremonies,
+    // The config has more ceremonies than its limit allows
+    TooManyCeremonies {
+        max: usize,
+        got: usize,
+    },
+    // A ceremony needs at least 2 powers in each group
+    NotEnoughPowers {
+        ceremony: usize,
+        group: Group,
+        got: usize,
+    },
+    // A ceremony has more powers in a group than the limit allows
+    TooManyPowers {
+        ceremony: usize,
+        group: Group,
+        max: usize,
+        got: usize,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NoCeremonies => write!(f, "no ceremonies"),
+            ConfigError::TooManyCeremonies { max, got } => {
+                write!(f, "expected at most {} ceremonies, got {}", max, got)
+            }
+            ConfigError::NotEnoughPowers {
+                ceremony,
+                group,
+                got,
+            } => write!(
+                f,
+                "ceremony {}: expected at least 2 {} powers, got {}",
+                ceremony, group, got
+            ),
+            ConfigError::TooManyPowers {
+                ceremony,
+                group,
+                max,
+                got,
+            } => write!(
+                f,
+                "ceremony {}: expected at most {} {} powers, got {}",
+                ceremony, max, group, got
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// The reason why a transcript or contribution could not be decoded
+// `ceremony` is the index of the ceremony in the batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchDeserialiseError {
+    // The SRS for the ceremony could not be decoded
+    InvalidSRS {
+        ceremony: usize,
+        error: DeserialiseError,
+    },
+    // A transcript must have a witness for each ceremony
+    MissingWitness {
+        ceremony: usize,
+    },
+    // The witness for the ceremony could not be decoded
+    InvalidWitness {
+        ceremony: usize,
+        error: DeserialiseError,
+    },
+    // The ceremonies do not match the config, or the config is not valid
+    ConfigMismatch(TranscriptVerificationError),
+    // A part of the transcript that is not specific to one ceremony could not be decoded
+    InvalidEncoding(DeserialiseError),
+}
+
+impl fmt::Display for BatchDeserialiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchDeserialiseError::InvalidSRS { ceremony, error } => {
+                write!(f, "ceremony {}: invalid SRS: {}", ceremony, error)
+            }
+            BatchDeserialiseError::MissingWitness { ceremony } => {
+                write!(f, "ceremony {}: missing witness", ceremony)
+            }
+            BatchDeserialiseError::InvalidWitness { ceremony, error } => {
+                write!(f, "ceremony {}: invalid witness: {}", ceremony, error)
+            }
+            BatchDeserialiseError::ConfigMismatch(error) => {
+                write!(f, "does not match the config: {}", error)
+            }
+            BatchDeserialiseError::InvalidEncoding(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BatchDeserialiseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BatchDeserialiseError::InvalidSRS { error, .. }
+            | BatchDeserialiseError::InvalidWitness { error, .. }
+            | BatchDeserialiseError::InvalidEncoding(error) => Some(error),
+            BatchDeserialiseError::ConfigMismatch(error) => Some(error),
+            BatchDeserialiseError::MissingWitness { .. } => None,
+        }
+    }
+}
 
+// The reason why the coordinator did not accept a contribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinatorError {
+    // The participant is not the one whose turn it is
+    NotYourTurn,
+    // The contribution arrived after the participant's deadline
+    DeadlineExpired,
+    // The updated transcript or the update proofs were not valid
+    InvalidContribution(TranscriptVerificationError),
+    // The identity signature for the ceremony does not match the pot pubkey in its update proof
+    InvalidIdentitySignature { ceremony: usize },
+}
 
-pub const NUM_CEREMONIES: usize = 4;
+impl fmt::Display for CoordinatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinatorError::NotYourTurn => write!(f, "it is not this participant's turn"),
+            CoordinatorError::DeadlineExpired => {
+                write!(f, "the contribution arrived after the deadline")
+            }
+            CoordinatorError::InvalidContribution(error) => {
+                write!(f, "invalid contribution: {}", error)
+            }
+            CoordinatorError::InvalidIdentitySignature { ceremony } => {
+                write!(f, "invalid identity signature for ceremony {}", ceremony)
+            }
+        }
+    }
+}
 
-pub const CEREMONIES: [Parameters; NUM_CEREMONIES] = [
-    Parameters {
-        num_g1_elements_needed: 4096,
-        num_g2_elements_needed: 65,
+impl std::error::Error for CoordinatorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CoordinatorError::InvalidContribution(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// The reason why a message could not be sent or received
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportError {
+    // The other end of the connection has gone away
+    Disconnected,
+    // The connection failed
+    Io(std::io::ErrorKind),
+    // The length of a message is over the limit
+    MessageTooLarge {
+        max: u64,
+        got: u64,
     },
-    Parameters {
-        num_g1_elements_needed: 8192,
-        num_g2_elements_needed: 65,
+    // The message is not valid JSON, or is not one of the message types
+    InvalidJson,
+    // The transcript in the message could not be decoded
+    InvalidTranscript(BatchDeserialiseError),
+    // An update proof in the message could not be decoded
+    InvalidUpdateProof {
+        index: usize,
+        error: DeserialiseError,
     },
-    Parameters {
-        num_g1_elements_needed: 16384,
-        num_g2_elements_needed: 65,
+    // An identity signature in the message could not be decoded
+    InvalidIdentitySignature {
+        index: usize,
+        error: PointDeserialiseError,
     },
-    Parameters {
-        num_g1_elements_needed: 32768,
-        num_g2_elements_needed: 65,
+    // The receipt in the message could not be decoded
+    InvalidReceipt(ReceiptDeserialiseError),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::Disconnected => write!(f, "the connection was closed"),
+            TransportError::Io(kind) => write!(f, "connection error: {}", kind),
+            TransportError::MessageTooLarge { max, got } => write!(
+                f,
+                "expected a message of at most {} bytes, got {}",
+                max, got
+            ),
+            TransportError::InvalidJson => write!(f, "invalid message"),
+            TransportError::InvalidTranscript(error) => {
+                write!(f, "invalid transcript: {}", error)
+            }
+            TransportError::InvalidUpdateProof { index, error } => {
+                write!(f, "invalid update proof {}: {}", index, error)
+            }
+            TransportError::InvalidIdentitySignature { index, error } => {
+                write!(f, "invalid identity signature {}: {}", index, error)
+            }
+            TransportError::InvalidReceipt(error) => write!(f, "invalid receipt: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransportError::InvalidTranscript(error) => Some(error),
+            TransportError::InvalidUpdateProof { error, .. } => Some(error),
+            TransportError::InvalidIdentitySignature { error, .. } => Some(error),
+            TransportError::InvalidReceipt(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TransportError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::UnexpectedEof
+            | std::io::ErrorKind::BrokenPipe
+            | std::io::ErrorKind::ConnectionReset => TransportError::Disconnected,
+            kind => TransportError::Io(kind),
+        }
+    }
+}
+
+// The reason why a contributor refused to update a transcript
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContributorError {
+    // The transcript does not have the ceremonies in the config
+    InvalidTranscript(TranscriptVerificationError),
+    // A point in the transcript is not in the prime order subgroup, so the contributor
+    // must not attest to it
+    SubgroupCheckFailed,
+}
+
+impl fmt::Display for ContributorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContributorError::InvalidTranscript(error) => {
+                write!(f, "invalid transcript: {}", error)
+            }
+            ContributorError::SubgroupCheckFailed => {
+                write!(f, "the transcript failed the subgroup check")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContributorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ContributorError::InvalidTranscript(error) => Some(error),
+            ContributorError::SubgroupCheckFailed => None,
+        }
+    }
+}
+
+// The reason why an inclusion receipt could not be decoded from its JSON form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiptDeserialiseError {
+    // An update proof could not be decoded
+    InvalidUpdateProof {
+        index: usize,
+        error: DeserialiseError,
     },
-];
\ No newline at end of file
+    // The transcript digest was not a 0x prefixed hex string of 32 bytes
+    InvalidDigest,
+    // The coordinator's signature could not be decoded
+    InvalidSignature(PointDeserialiseError),
+}
+
+impl fmt::Display for ReceiptDeserialiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptDeserialiseError::InvalidUpdateProof { index, error } => {
+                write!(f, "invalid update proof {}: {}", index, error)
+            }
+            ReceiptDeserialiseError::InvalidDigest => write!(f, "invalid transcript digest"),
+            ReceiptDeserialiseError::InvalidSignature(error) => {
+                write!(f, "invalid signature: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReceiptDeserialiseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReceiptDeserialiseError::InvalidUpdateProof { error, .. } => Some(error),
+            ReceiptDeserialiseError::InvalidDigest => None,
+            ReceiptDeserialiseError::InvalidSignature(error) => Some(error),
+        }
+    }
+}
+
+// The config and secrets shared by the tests of the sdk modules
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::{sdk::config::CeremonyConfig, srs::Parameters};
+
+    pub(crate) fn test_config() -> CeremonyConfig {
+        CeremonyConfig {
+            name: "test".to_string(),
+            ceremonies: vec![Parameters::new(8, 2), Parameters::new(4, 3)],
+            ..CeremonyConfig::default()
+        }
+    }
+
+    // One secret for each ceremony in `test_config`
+    pub(crate) fn test_secrets() -> Vec<String> {
+        vec!["0x1234".to_string(), "0x5678".to_string()]
+    }
+}
@@ -0,0 +1,309 @@
+// A receipt that the coordinator signs when it accepts a contribution.
+//
+// It records the participant, the position of their contribution in the transcript, their
+// update proofs and the digest of the transcript after it was recorded. If the final transcript
+// does not have the contribution at that position, the participant can show the signed receipt
+// to prove that the coordinator dropped them.
+//
+// The receipt is a BLS signature in G1, made with the coordinator's key, in the same way as
+// the identity signatures. The coordinator signs a receipt for each contribution it accepts,
+// and sends it to the participant.
+use ark_bls12_381::G1Projective;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    binary_serialisation::write_u64,
+    keypair::{verify_bls_g1_signature, PrivateKey, PublicKey},
+    sdk::{coordinator::AcceptedContribution, transcript::Transcript, ReceiptDeserialiseError},
+    serialisation::{deserialise_identity_signature, serialise_identity_signature, PointEncoding},
+    update_proof::UpdateProof,
+};
+
+// The domain separation tag used when hashing a receipt to a point
+pub const INCLUSION_RECEIPT_DST: &[u8] =
+    b"SMALL_POWERS_OF_TAU_INCLUSION_RECEIPT_BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+// The coordinator's long-lived key, which it signs receipts with.
+//
+// This is kept apart from the `PrivateKey` that a contributor uses for a single update.
+pub struct CoordinatorKey(PrivateKey);
+
+impl CoordinatorKey {
+    // This function should only be used for testing purposes
+    #[cfg(test)]
+    pub(crate) fn from_u64(int: u64) -> Self {
+        CoordinatorKey(PrivateKey::from_u64(int))
+    }
+    // Creates a key using entropy from a RNG
+    pub fn rand<R: Rng>(rand: R) -> Self {
+        CoordinatorKey(PrivateKey::rand(rand))
+    }
+    // Creates a key using bytes
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        CoordinatorKey(PrivateKey::from_bytes(bytes))
+    }
+
+    // The key that participants check receipts against
+    pub fn public_key(&self) -> PublicKey {
+        self.0.to_public()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionReceipt {
+    pub participant_id: String,
+    // The index of the contribution in the transcript witnesses and participant ids
+    pub position: usize,
+    // One update proof for each ceremony in the batch
+    pub update_proofs: Vec<UpdateProof>,
+    // The digest of the transcript after the contribution was recorded
+    pub transcript_digest: [u8; 32],
+    pub signature: G1Projective,
+}
+
+impl InclusionReceipt {
+    // Signs a receipt for a contribution that the coordinator accepted
+    pub fn sign(coordinator_key: &CoordinatorKey, accepted: &AcceptedContribution) -> Self {
+        let message = receipt_message(
+            &accepted.participant_id,
+            accepted.position,
+            &accepted.update_proofs,
+            &accepted.transcript_digest,
+        );
+        let signature = coordinator_key
+            .0
+            .sign_bls_g1(&message, INCLUSION_RECEIPT_DST);
+
+        InclusionReceipt {
+            participant_id: accepted.participant_id.clone(),
+            position: accepted.position,
+            update_proofs: accepted.update_proofs.clone(),
+            transcript_digest: accepted.transcript_digest,
+            signature,
+        }
+    }
+
+    // Checks that the receipt was signed by the coordinator
+    //
+    // This checks that e(signature, G2) == e(H(receipt), coordinator_key)
+    pub fn verify(&self, coordinator_key: &PublicKey) -> bool {
+        let message = receipt_message(
+            &self.participant_id,
+            self.position,
+            &self.update_proofs,
+            &self.transcript_digest,
+        );
+        verify_bls_g1_signature(
+            &message,
+            INCLUSION_RECEIPT_DST,
+            coordinator_key.point(),
+            self.signature,
+        )
+    }
+
+    // The pot pubkey of the participant in each ceremony
+    //
+    // Returns None if any of the commitments in the update proofs is not in the prime order
+    // subgroup, which can happen for a receipt that was decoded from JSON
+    pub fn pot_pubkeys(&self) -> Option<Vec<PublicKey>> {
+        self.update_proofs
+            .iter()
+            .map(UpdateProof::public_key)
+            .collect()
+    }
+
+    // Checks that the transcript records the contribution at the position in the receipt.
+    //
+    // This does not verify the transcript, which should be done with `transcript_verify_witnesses`
+    pub fn is_included_in(&self, transcript: &Transcript) -> bool {
+        if transcript.witnesses.len() != self.update_proofs.len() {
+            return false;
+        }
+        if transcript.participant_ids.get(self.position) != Some(&self.participant_id) {
+            return false;
+        }
+
+        transcript
+            .witnesses
+            .iter()
+            .zip(&self.update_proofs)
+            .all(|(witness, update_proof)| {
+                witness.pot_pubkeys.get(self.position) == Some(&update_proof.commitment_to_secret)
+                    && witness.running_products.get(self.position)
+                        == Some(&update_proof.new_accumulated_point)
+            })
+    }
+}
+
+// Encodes everything in the receipt except the signature, with length prefixes so that no two
+// receipts have the same encoding
+fn receipt_message(
+    participant_id: &str,
+    position: usize,
+    update_proofs: &[UpdateProof],
+    transcript_digest: &[u8; 32],
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    // Writing into a vector cannot fail
+    write_u64(&mut message, participant_id.len() as u64).expect("writing cannot fail");
+    message.extend_from_slice(participant_id.as_bytes());
+    write_u64(&mut message, position as u64).expect("writing cannot fail");
+    write_u64(&mut message, update_proofs.len() as u64).expect("writing cannot fail");
+    for update_proof in update_proofs {
+        update_proof
+            .write_to(&mut message, PointEncoding::Compressed)
+            .expect("writing cannot fail");
+    }
+    message.extend_from_slice(transcript_digest);
+    message
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InclusionReceiptJSON {
+    #[serde(rename = "participantId")]
+    pub participant_id: String,
+    pub position: usize,
+    #[serde(rename = "updateProofs")]
+    pub update_proofs: Vec<[String; 2]>,
+    #[serde(rename = "transcriptDigest")]
+    pub transcript_digest: String,
+    pub signature: String,
+}
+
+impl From<&InclusionReceipt> for InclusionReceiptJSON {
+    fn from(receipt: &InclusionReceipt) -> Self {
+        InclusionReceiptJSON {
+            participant_id: receipt.participant_id.clone(),
+            position: receipt.position,
+            update_proofs: receipt
+                .update_proofs
+                .iter()
+                .map(UpdateProof::serialise)
+                .collect(),
+            transcript_digest: format!("0x{}", hex::encode(receipt.transcript_digest)),
+            signature: serialise_identity_signature(&receipt.signature),
+        }
+    }
+}
+
+impl TryFrom<&InclusionReceiptJSON> for InclusionReceipt {
+    type Error = ReceiptDeserialiseError;
+
+    fn try_from(receipt_json: &InclusionReceiptJSON) -> Result<Self, Self::Error> {
+        let update_proofs = receipt_json
+            .update_proofs
+            .iter()
+            .enumerate()
+            .map(|(index, update_proof)| {
+                UpdateProof::deserialise(update_proof.clone())
+                    .map_err(|error| ReceiptDeserialiseError::InvalidUpdateProof { index, error })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut transcript_digest = [0u8; 32];
+        let digest_hex = receipt_json
+            .transcript_digest
+            .strip_prefix("0x")
+            .ok_or(ReceiptDeserialiseError::InvalidDigest)?;
+        hex::decode_to_slice(digest_hex, &mut transcript_digest)
+            .map_err(|_| ReceiptDeserialiseError::InvalidDigest)?;
+
+        let signature = deserialise_identity_signature(&receipt_json.signature)
+            .map_err(ReceiptDeserialiseError::InvalidSignature)?;
+
+        Ok(InclusionReceipt {
+            participant_id: receipt_json.participant_id.clone(),
+            position: receipt_json.position,
+            update_proofs,
+            transcript_digest,
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdk::{
+        coordinator::{ContributionSignatures, Coordinator, ManualClock},
+        test_utils::{test_config, test_secrets},
+        transcript::update_transcript_with_secrets,
+    };
+    use crate::serialisation::g2_to_hex_string;
+    use ark_bls12_381::{Fq2, G2Affine};
+    use ark_ff::One;
+    use std::time::Duration;
+
+    #[test]
+    fn inclusion_receipt_smoke() {
+        let config = test_config();
+        let starting_transcript = Transcript::with_config(&config).unwrap();
+        let mut coordinator = Coordinator::new(
+            config.clone(),
+            starting_transcript.clone(),
+            CoordinatorKey::from_u64(42),
+            ManualClock::default(),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+
+        coordinator.join("alice".to_string());
+        let turn = coordinator.next_turn().unwrap();
+        let (transcript, update_proofs, pot_pubkeys) =
+            update_transcript_with_secrets(&config, turn.transcript, test_secrets()).unwrap();
+        let receipt = coordinator
+            .submit(
+                "alice",
+                transcript,
+                update_proofs,
+                ContributionSignatures::unsigned(config.ceremonies.len()),
+            )
+            .unwrap();
+
+        let coordinator_key = CoordinatorKey::from_u64(42).public_key();
+        assert_eq!(
+            receipt,
+            InclusionReceipt::sign(&CoordinatorKey::from_u64(42), &coordinator.history()[0])
+        );
+        assert!(receipt.verify(&coordinator_key));
+        assert!(!receipt.verify(&CoordinatorKey::from_u64(43).public_key()));
+        assert_eq!(receipt.pot_pubkeys(), Some(pot_pubkeys));
+        assert_eq!(receipt.transcript_digest, coordinator.transcript().digest());
+
+        // Changing the position invalidates the signature
+        let mut moved = receipt.clone();
+        moved.position += 1;
+        assert!(!moved.verify(&coordinator_key));
+
+        // The receipt shows whether a transcript includes the contribution
+        assert!(receipt.is_included_in(coordinator.transcript()));
+        assert!(!receipt.is_included_in(&starting_transcript));
+        assert!(!moved.is_included_in(coordinator.transcript()));
+
+        let json = serde_json::to_string(&InclusionReceiptJSON::from(&receipt)).unwrap();
+        let receipt_json: InclusionReceiptJSON = serde_json::from_str(&json).unwrap();
+        assert_eq!(InclusionReceipt::try_from(&receipt_json), Ok(receipt));
+
+        let mut bad_json = serde_json::from_str::<InclusionReceiptJSON>(&json).unwrap();
+        bad_json.transcript_digest = "0x1234".to_string();
+        assert_eq!(
+            InclusionReceipt::try_from(&bad_json),
+            Err(ReceiptDeserialiseError::InvalidDigest)
+        );
+
+        // A receipt from JSON may have a commitment outside of the subgroup, which is not
+        // returned as a pot pubkey
+        let mut x = Fq2::one();
+        let point = loop {
+            if let Some(point) = G2Affine::get_point_from_x(x, true) {
+                break point;
+            }
+            x += Fq2::one();
+        };
+        let mut bad_json = receipt_json;
+        bad_json.update_proofs[1][0] = g2_to_hex_string(&point, PointEncoding::Compressed);
+        let bad_receipt = InclusionReceipt::try_from(&bad_json).unwrap();
+        assert_eq!(bad_receipt.pot_pubkeys(), None);
+    }
+}
@@ -1,146 +1,676 @@
+use std::io::{Read, Write};
+
 use ark_bls12_381::Fr;
-use ark_ff::PrimeField;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::{
-    keypair::PrivateKey,
-    srs::SRS,
+    binary_serialisation::{
+        read_header, read_strings, read_u64, write_header, write_strings, write_u64,
+        TRANSCRIPT_MAGIC,
+    },
+    error::{DeserialiseError, VerificationError},
+    key_derivation::EntropyBundle,
+    keypair::{PrivateKey, PublicKey},
+    sdk::{
+        batch::{decode_random_elements, decode_secrets, Batch},
+        config::CeremonyConfig,
+        BatchDeserialiseError, ConfigError, TranscriptVerificationError,
+    },
+    serialisation::{PointEncoding, SRSJson},
+    srs::{Parameters, SRS},
     update_proof::UpdateProof,
-    serialisation::SRSJson,
-    sdk::{NUM_CEREMONIES, CEREMONIES},
+    witness::Witness,
 };
 
+// The number of ceremonies that we allocate space for up front, when reading a transcript
+const MAX_PREALLOCATED_CEREMONIES: usize = 64;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Transcript {
-    pub transcripts: [SRS; NUM_CEREMONIES],
+    // The current SRS of each ceremony
+    pub batch: Batch,
+    // The history of contributions to each SRS
+    pub witnesses: Vec<Witness>,
+    // The identity of each participant and their ECDSA signature, in the order that they contributed.
+    // Like the witnesses, the first entry is for the starting transcript and is empty.
+    pub participant_ids: Vec<String>,
+    pub participant_ecdsa_signatures: Vec<String>,
 }
 
 impl Default for Transcript {
     fn default() -> Self {
+        Transcript::from_batch(Batch::default())
+    }
+}
+
+impl Transcript {
+    // Creates the starting transcript for a batch of ceremonies
+    //
+    // Returns None if any of the ceremonies has less than two powers in a group
+    pub fn new(parameters: &[Parameters]) -> Option<Transcript> {
+        Batch::new(parameters).map(Transcript::from_batch)
+    }
+
+    // Creates the starting transcript for the ceremonies in the config
+    //
+    // The config is validated before any SRS is allocated
+    pub fn with_config(config: &CeremonyConfig) -> Result<Transcript, ConfigError> {
+        Batch::with_config(config).map(Transcript::from_batch)
+    }
+
+    fn from_batch(batch: Batch) -> Transcript {
         Transcript {
-            transcripts: [
-                SRS::new(CEREMONIES[0]).unwrap(),
-                SRS::new(CEREMONIES[1]).unwrap(),
-                SRS::new(CEREMONIES[2]).unwrap(),
-                SRS::new(CEREMONIES[3]).unwrap(),
-            ],
+            witnesses: vec![Witness::default(); batch.len()],
+            batch,
+            participant_ids: vec![String::new()],
+            participant_ecdsa_signatures: vec![String::new()],
         }
     }
 }
 
+// Updates each ceremony with a private key derived from the entropy bundle
+//
+// Returns None if the transcript does not have the ceremonies in the config
 pub fn update_transcript(
-    mut transcript: Transcript,
-    secrets: [String; NUM_CEREMONIES],
-) -> Option<(Transcript, [UpdateProof; NUM_CEREMONIES])> {
-    // Check that the parameters for each SRS is correct
-    for (srs, params) in transcript.transcripts.iter().zip(CEREMONIES.into_iter()) {
-        if srs.g1_elements().len() != params.num_g1_elements_needed {
-            return None;
-        }
-        if srs.g2_elements().len() != params.num_g2_elements_needed {
-            return None;
-        }
-    }
+    config: &CeremonyConfig,
+    transcript: Transcript,
+    entropy: &EntropyBundle,
+) -> Option<(Transcript, Vec<UpdateProof>, Vec<PublicKey>)> {
+    let private_keys = entropy.derive_private_keys(transcript.batch.len());
+    update_transcript_with_keys(config, transcript, private_keys)
+}
 
-    let mut update_proofs = Vec::with_capacity(NUM_CEREMONIES);
+// Like `update_transcript`, but the caller supplies a 0x prefixed hex secret for each ceremony
+pub fn update_transcript_with_secrets(
+    config: &CeremonyConfig,
+    transcript: Transcript,
+    mut secrets: Vec<String>,
+) -> Option<(Transcript, Vec<UpdateProof>, Vec<PublicKey>)> {
+    let private_keys = decode_secrets(&mut secrets)?;
+    update_transcript_with_keys(config, transcript, private_keys)
+}
 
-    for (i, secret_hex) in secrets.into_iter().enumerate() {
-        if let Some(stripped_point_json) = secret_hex.strip_prefix("0x") {
-            let bytes = hex::decode(stripped_point_json).ok()?;
-            let priv_key = PrivateKey::from_bytes(&bytes);
+fn update_transcript_with_keys(
+    config: &CeremonyConfig,
+    mut transcript: Transcript,
+    private_keys: Vec<PrivateKey>,
+) -> Option<(Transcript, Vec<UpdateProof>, Vec<PublicKey>)> {
+    config.check_batch(&transcript.batch).ok()?;
+    if transcript.witnesses.len() != transcript.batch.len() {
+        return None;
+    }
 
-            let update_proof = transcript.transcripts[i].update(priv_key);
-            update_proofs.push(update_proof);
-        } else {
-            return None;
-        }
+    let public_keys = private_keys.iter().map(PublicKey::from).collect();
+    let update_proofs = transcript.batch.update(private_keys)?;
+    for (witness, update_proof) in transcript.witnesses.iter_mut().zip(&update_proofs) {
+        witness.push(update_proof, None);
     }
 
-    let update_proofs: [UpdateProof; NUM_CEREMONIES] = update_proofs.try_into().unwrap();
+    // The identity of the participant is not known here, so an empty entry is
+    // recorded which the coordinator can fill in
+    transcript.participant_ids.push(String::new());
+    transcript.participant_ecdsa_signatures.push(String::new());
 
-    Some((transcript, update_proofs))
+    Some((transcript, update_proofs, public_keys))
 }
 
-pub fn transcript_subgroup_check(transcript: Transcript) -> bool {
-    for srs in &transcript.transcripts {
-        if !srs.subgroup_check() {
-            return false;
-        }
-    }
-    true
+pub fn transcript_subgroup_check(
+    transcript: &Transcript,
+) -> Result<(), (usize, VerificationError)> {
+    transcript.batch.subgroup_check()
 }
 
 pub fn transcript_verify_update(
+    config: &CeremonyConfig,
     old_transcript: &Transcript,
     new_transcript: &Transcript,
-    update_proofs: &[UpdateProof; NUM_CEREMONIES],
-    random_hex_elements: [String; NUM_CEREMONIES],
-) -> bool {
-    for i in 0..NUM_CEREMONIES {
-        // Decode random hex string into a field element
-        //
-        //
-        let hex_str = &random_hex_elements[i];
-        let hex_str = if let Some(stripped_random_hex) = hex_str.strip_prefix("0x") {
-            stripped_random_hex
-        } else {
-            return false;
-        };
+    update_proofs: &[UpdateProof],
+    random_hex_elements: &[String],
+) -> Result<(), TranscriptVerificationError> {
+    config.check_batch(&old_transcript.batch)?;
+    let random_elements = decode_random_elements(random_hex_elements)?;
+    Batch::verify_update(
+        &old_transcript.batch,
+        &new_transcript.batch,
+        update_proofs,
+        &random_elements,
+    )
+}
 
-        let element = match hex::decode(hex_str) {
-            Ok(bytes) => Fr::from_be_bytes_mod_order(&bytes),
-            Err(_) => return false,
-        };
+// Like `transcript_verify_update`, but the random element for each ceremony is derived by hashing
+// the transcripts and update proofs, so the caller does not need to supply them
+pub fn transcript_verify_update_fiat_shamir(
+    config: &CeremonyConfig,
+    old_transcript: &Transcript,
+    new_transcript: &Transcript,
+    update_proofs: &[UpdateProof],
+) -> Result<(), TranscriptVerificationError> {
+    config.check_batch(&old_transcript.batch)?;
+    Batch::verify_update_fiat_shamir(&old_transcript.batch, &new_transcript.batch, update_proofs)
+}
 
-        // Verify update
-        //
-        let proof = update_proofs[i];
-        let before = &old_transcript.transcripts[i];
-        let after = &new_transcript.transcripts[i];
-        if !SRS::verify_update(before, after, &proof, element) {
-            return false;
-        };
+// Verify the whole transcript using the running products and pot pubkeys in its witnesses,
+// without needing the update proofs or any of the previous transcripts
+pub fn transcript_verify_witnesses(
+    config: &CeremonyConfig,
+    transcript: &Transcript,
+    random_hex_elements: &[String],
+) -> Result<(), TranscriptVerificationError> {
+    config.check_batch(&transcript.batch)?;
+    let random_elements = decode_random_elements(random_hex_elements)?;
+    transcript.verify_witnesses(&random_elements)
+}
+
+impl Transcript {
+    // Verify that each SRS is the result of the contributions recorded in its witness
+    pub fn verify_witnesses(
+        &self,
+        random_elements: &[Fr],
+    ) -> Result<(), TranscriptVerificationError> {
+        for got in [self.witnesses.len(), random_elements.len()] {
+            if got != self.batch.len() {
+                return Err(TranscriptVerificationError::WrongNumberOfCeremonies {
+                    expected: self.batch.len(),
+                    got,
+                });
+            }
+        }
+
+        for (i, ((srs, witness), random_element)) in self
+            .batch
+            .ceremonies()
+            .iter()
+            .zip(&self.witnesses)
+            .zip(random_elements)
+            .enumerate()
+        {
+            witness.verify(srs, *random_element).map_err(|error| {
+                TranscriptVerificationError::InvalidWitness { ceremony: i, error }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Transcript {
+    // Writes the transcript in the binary format, using the given point encoding
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+        encoding: PointEncoding,
+    ) -> std::io::Result<()> {
+        write_header(writer, TRANSCRIPT_MAGIC)?;
+        write_u64(writer, self.batch.len() as u64)?;
+        for (srs, witness) in self.batch.ceremonies().iter().zip(&self.witnesses) {
+            srs.write_to(writer, encoding)?;
+            witness.write_to(writer, encoding)?;
+        }
+        write_strings(writer, &self.participant_ids)?;
+        write_strings(writer, &self.participant_ecdsa_signatures)
+    }
+
+    // The SHA-256 hash of the transcript in the binary format, with compressed points
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        self.write_to(&mut hasher, PointEncoding::Compressed)
+            .expect("hashing cannot fail");
+        hasher.finalize().into()
+    }
+
+    // Writes the transcript in the binary format, using the encoding in the config
+    pub fn write_with_config<W: Write>(
+        &self,
+        writer: &mut W,
+        config: &CeremonyConfig,
+    ) -> std::io::Result<()> {
+        self.write_to(writer, config.encoding)
+    }
+
+    // Reads a transcript in the binary format
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Transcript, DeserialiseError> {
+        read_header(reader, TRANSCRIPT_MAGIC)?;
+        let num_ceremonies = read_u64(reader)?;
+
+        let capacity = (num_ceremonies as usize).min(MAX_PREALLOCATED_CEREMONIES);
+        let mut ceremonies = Vec::with_capacity(capacity);
+        let mut witnesses = Vec::with_capacity(capacity);
+        for _ in 0..num_ceremonies {
+            ceremonies.push(SRS::read_from(reader)?);
+            witnesses.push(Witness::read_from(reader)?);
+        }
+        let participant_ids = read_strings(reader)?;
+        let participant_ecdsa_signatures = read_strings(reader)?;
+
+        Ok(Self {
+            batch: Batch::from_ceremonies(ceremonies),
+            witnesses,
+            participant_ids,
+            participant_ecdsa_signatures,
+        })
+    }
+
+    // Like `read_from`, but the number of ceremonies and the number of powers in each SRS header
+    // are checked against the config before any point is read
+    pub fn read_with_config<R: Read>(
+        reader: &mut R,
+        config: &CeremonyConfig,
+    ) -> Result<Transcript, BatchDeserialiseError> {
+        config.validate().map_err(|error| {
+            BatchDeserialiseError::ConfigMismatch(TranscriptVerificationError::InvalidConfig(error))
+        })?;
+        read_header(reader, TRANSCRIPT_MAGIC).map_err(BatchDeserialiseError::InvalidEncoding)?;
+        let num_ceremonies = read_u64(reader).map_err(BatchDeserialiseError::InvalidEncoding)?;
+        if num_ceremonies != config.ceremonies.len() as u64 {
+            return Err(BatchDeserialiseError::ConfigMismatch(
+                TranscriptVerificationError::WrongNumberOfCeremonies {
+                    expected: config.ceremonies.len(),
+                    got: num_ceremonies as usize,
+                },
+            ));
+        }
+
+        let mut ceremonies = Vec::with_capacity(config.ceremonies.len());
+        let mut witnesses = Vec::with_capacity(config.ceremonies.len());
+        for (ceremony, parameters) in config.ceremonies.iter().enumerate() {
+            let srs = SRS::read_with_parameters(reader, Some(*parameters))
+                .map_err(|error| BatchDeserialiseError::InvalidSRS { ceremony, error })?;
+            ceremonies.push(srs);
+            let witness = Witness::read_from(reader)
+                .map_err(|error| BatchDeserialiseError::InvalidWitness { ceremony, error })?;
+            witnesses.push(witness);
+        }
+        let participant_ids =
+            read_strings(reader).map_err(BatchDeserialiseError::InvalidEncoding)?;
+        let participant_ecdsa_signatures =
+            read_strings(reader).map_err(BatchDeserialiseError::InvalidEncoding)?;
+
+        Ok(Self {
+            batch: Batch::from_ceremonies(ceremonies),
+            witnesses,
+            participant_ids,
+            participant_ecdsa_signatures,
+        })
     }
 
-    true
+    // Like `Transcript::try_from`, but the number of ceremonies and the number of powers that
+    // each SRS claims to have are checked against the config before any point is decoded
+    pub fn from_json_with_config(
+        transcript_json: &TranscriptJSON,
+        config: &CeremonyConfig,
+    ) -> Result<Transcript, BatchDeserialiseError> {
+        let parameters: Vec<_> = transcript_json
+            .transcripts
+            .iter()
+            .map(SRSJson::parameters)
+            .collect();
+        config
+            .check_parameters(&parameters)
+            .map_err(BatchDeserialiseError::ConfigMismatch)?;
+        Transcript::try_from(transcript_json)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TranscriptJSON {
-    pub transcripts: [SRSJson; NUM_CEREMONIES],
+    pub transcripts: Vec<SRSJson>,
+    #[serde(rename = "participantIds", default)]
+    pub participant_ids: Vec<String>,
+    #[serde(rename = "participantEcdsaSignatures", default)]
+    pub participant_ecdsa_signatures: Vec<String>,
 }
 
-impl From<&Transcript> for TranscriptJSON {
-    fn from(transcript: &Transcript) -> Self {
+impl TranscriptJSON {
+    pub fn with_encoding(transcript: &Transcript, encoding: PointEncoding) -> Self {
         let transcripts_json = transcript
-            .transcripts
-            // TODO: can remove clone but will need to try_into for array size
-            .clone()
-            .map(|srs| SRSJson::from(&srs));
+            .batch
+            .ceremonies()
+            .iter()
+            .zip(&transcript.witnesses)
+            .map(|(srs, witness)| SRSJson::with_witness_and_encoding(srs, witness, encoding))
+            .collect();
         Self {
             transcripts: transcripts_json,
+            participant_ids: transcript.participant_ids.clone(),
+            participant_ecdsa_signatures: transcript.participant_ecdsa_signatures.clone(),
         }
     }
+
+    // Encodes the points with the encoding in the config
+    pub fn with_config(transcript: &Transcript, config: &CeremonyConfig) -> Self {
+        TranscriptJSON::with_encoding(transcript, config.encoding)
+    }
+}
+
+impl From<&Transcript> for TranscriptJSON {
+    fn from(transcript: &Transcript) -> Self {
+        TranscriptJSON::with_encoding(transcript, PointEncoding::Compressed)
+    }
 }
 
-impl From<&TranscriptJSON> for Transcript {
-    fn from(transcript_json: &TranscriptJSON) -> Self {
-        // TODO: find a cleaner way to write this
-        let transcripts_option: [Option<SRS>; NUM_CEREMONIES] = transcript_json
+impl TryFrom<&TranscriptJSON> for Transcript {
+    type Error = BatchDeserialiseError;
+
+    fn try_from(transcript_json: &TranscriptJSON) -> Result<Self, Self::Error> {
+        let batch = Batch::from_json(&transcript_json.transcripts)?;
+
+        // A transcript must record the history of each SRS
+        let witnesses = transcript_json
             .transcripts
-            .clone()
-            .map(|srs_json| (&srs_json).into());
+            .iter()
+            .enumerate()
+            .map(|(ceremony, srs_json)| {
+                let witness_json = srs_json
+                    .witness()
+                    .ok_or(BatchDeserialiseError::MissingWitness { ceremony })?;
+                Witness::try_from(witness_json)
+                    .map_err(|error| BatchDeserialiseError::InvalidWitness { ceremony, error })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let mut transcripts = Vec::new();
+        Ok(Self {
+            batch,
+            witnesses,
+            participant_ids: transcript_json.participant_ids.clone(),
+            participant_ecdsa_signatures: transcript_json.participant_ecdsa_signatures.clone(),
+        })
+    }
+}
 
-        for optional_srs in transcripts_option {
-            match optional_srs {
-                Some(srs) => transcripts.push(srs),
-                None => return Transcript::default(),
-            }
-        }
-        Self {
-            transcripts: transcripts.try_into().unwrap(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcript_binary_roundtrip() {
+        let transcript = Transcript::default();
+
+        let mut bytes = Vec::new();
+        transcript
+            .write_to(&mut bytes, PointEncoding::Uncompressed)
+            .unwrap();
+        let got = Transcript::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(got, transcript);
+    }
+
+    fn transcript_with_contribution() -> Transcript {
+        use ark_bls12_381::{G1Projective, G2Projective};
+        use ark_ec::ProjectiveCurve;
+        use ark_ff::PrimeField;
+
+        let mut transcript = Transcript::default();
+
+        // Updating the full transcript is slow, so we only record a contribution in the witness
+        let proof = UpdateProof {
+            commitment_to_secret: G2Projective::prime_subgroup_generator()
+                .mul(Fr::from(200u64).into_repr()),
+            new_accumulated_point: G1Projective::prime_subgroup_generator()
+                .mul(Fr::from(200u64).into_repr()),
+        };
+        let signature = G1Projective::prime_subgroup_generator().mul(Fr::from(42u64).into_repr());
+        for witness in &mut transcript.witnesses {
+            witness.push(&proof, Some(signature));
         }
+        transcript
+            .participant_ids
+            .push("eth|0x0000000000000000000000000000000000000000".to_string());
+        transcript
+            .participant_ecdsa_signatures
+            .push("0x1234".to_string());
+        transcript
+    }
+
+    #[test]
+    fn transcript_with_custom_ceremonies() {
+        let config = CeremonyConfig {
+            name: "test".to_string(),
+            ceremonies: vec![
+                Parameters::new(16, 2),
+                Parameters::new(8, 4),
+                Parameters::new(4, 2),
+            ],
+            ..CeremonyConfig::default()
+        };
+        let old_transcript = Transcript::with_config(&config).unwrap();
+        assert_eq!(old_transcript.batch.parameters(), config.ceremonies);
+        assert_eq!(old_transcript.witnesses.len(), 3);
+
+        let secrets = vec!["0x01".to_string(), "0x02".to_string(), "0x03".to_string()];
+        let (new_transcript, update_proofs, public_keys) =
+            update_transcript_with_secrets(&config, old_transcript.clone(), secrets).unwrap();
+        assert_eq!(public_keys[1], PrivateKey::from_u64(2).to_public());
+        assert_eq!(
+            public_keys[2].point(),
+            new_transcript.witnesses[2].pot_pubkeys[1]
+        );
+
+        assert!(transcript_subgroup_check(&new_transcript).is_ok());
+        assert!(transcript_verify_update_fiat_shamir(
+            &config,
+            &old_transcript,
+            &new_transcript,
+            &update_proofs
+        )
+        .is_ok());
+        let random_hex_elements = vec!["0x1234".to_string(); 3];
+        assert!(
+            transcript_verify_witnesses(&config, &new_transcript, &random_hex_elements).is_ok()
+        );
+        assert_eq!(
+            transcript_verify_witnesses(&config, &new_transcript, &random_hex_elements[..2]),
+            Err(TranscriptVerificationError::WrongNumberOfCeremonies {
+                expected: 3,
+                got: 2
+            })
+        );
+
+        // A contributor can instead derive the secrets from a bundle of entropy
+        let mut entropy = EntropyBundle::new();
+        entropy.add_user_entropy(b"some user entropy");
+        let (newer_transcript, update_proofs, _) =
+            update_transcript(&config, new_transcript.clone(), &entropy).unwrap();
+        assert!(transcript_verify_update_fiat_shamir(
+            &config,
+            &new_transcript,
+            &newer_transcript,
+            &update_proofs
+        )
+        .is_ok());
+        assert_eq!(newer_transcript.witnesses[0].len(), 3);
+
+        // There must be a secret for each ceremony
+        assert!(update_transcript_with_secrets(
+            &config,
+            new_transcript.clone(),
+            vec!["0x01".to_string()]
+        )
+        .is_none());
+
+        // The transcript must have the ceremonies in the config
+        let other_config = CeremonyConfig {
+            ceremonies: vec![Parameters::new(16, 2), Parameters::new(8, 4)],
+            ..config.clone()
+        };
+        assert_eq!(
+            transcript_verify_witnesses(&other_config, &new_transcript, &random_hex_elements),
+            Err(TranscriptVerificationError::WrongNumberOfCeremonies {
+                expected: 2,
+                got: 3
+            })
+        );
+        let invalid_config = CeremonyConfig {
+            ceremonies: vec![],
+            ..config
+        };
+        assert_eq!(
+            transcript_verify_witnesses(&invalid_config, &new_transcript, &random_hex_elements),
+            Err(TranscriptVerificationError::InvalidConfig(
+                ConfigError::NoCeremonies
+            ))
+        );
+    }
+
+    #[test]
+    fn transcript_encoding_with_config() {
+        use crate::error::{DeserialiseError, Group};
+
+        let config = CeremonyConfig {
+            name: "test".to_string(),
+            ceremonies: vec![Parameters::new(8, 2), Parameters::new(4, 3)],
+            encoding: PointEncoding::Uncompressed,
+            ..CeremonyConfig::default()
+        };
+        let transcript = Transcript::with_config(&config).unwrap();
+
+        // The points are written with the encoding in the config
+        let mut bytes = Vec::new();
+        transcript.write_with_config(&mut bytes, &config).unwrap();
+        let mut compressed_bytes = Vec::new();
+        transcript
+            .write_to(&mut compressed_bytes, PointEncoding::Compressed)
+            .unwrap();
+        assert!(bytes.len() > compressed_bytes.len());
+        assert_eq!(
+            Transcript::read_with_config(&mut bytes.as_slice(), &config).unwrap(),
+            transcript
+        );
+
+        let transcript_json = TranscriptJSON::with_config(&transcript, &config);
+        let value = serde_json::to_value(&transcript_json).unwrap();
+        let g1_hex = value["transcripts"][0]["powersOfTau"]["G1Powers"][0]
+            .as_str()
+            .unwrap();
+        assert_eq!(g1_hex.len(), 2 + 2 * 96);
+        assert_eq!(
+            Transcript::from_json_with_config(&transcript_json, &config).unwrap(),
+            transcript
+        );
+
+        // The headers are checked against the config before any point is decoded
+        let other_config = CeremonyConfig {
+            ceremonies: vec![Parameters::new(8, 2), Parameters::new(1 << 12, 3)],
+            ..config.clone()
+        };
+        assert_eq!(
+            Transcript::read_with_config(&mut bytes.as_slice(), &other_config),
+            Err(BatchDeserialiseError::InvalidSRS {
+                ceremony: 1,
+                error: DeserialiseError::WrongNumberOfPowers {
+                    group: Group::G1,
+                    expected: 1 << 12,
+                    got: 4
+                }
+            })
+        );
+        assert_eq!(
+            Transcript::from_json_with_config(&transcript_json, &other_config),
+            Err(BatchDeserialiseError::ConfigMismatch(
+                TranscriptVerificationError::ParametersMismatch { ceremony: 1 }
+            ))
+        );
+        let invalid_config = CeremonyConfig {
+            ceremonies: vec![],
+            ..config
+        };
+        assert_eq!(
+            Transcript::read_with_config(&mut bytes.as_slice(), &invalid_config),
+            Err(BatchDeserialiseError::ConfigMismatch(
+                TranscriptVerificationError::InvalidConfig(ConfigError::NoCeremonies)
+            ))
+        );
+    }
+
+    #[test]
+    fn transcript_binary_roundtrip_with_witness() {
+        let transcript = transcript_with_contribution();
+
+        let mut bytes = Vec::new();
+        transcript
+            .write_to(&mut bytes, PointEncoding::Uncompressed)
+            .unwrap();
+        let got = Transcript::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(got, transcript);
+    }
+
+    #[test]
+    fn transcript_json_roundtrip() {
+        let transcript = transcript_with_contribution();
+
+        let json = serde_json::to_string(&TranscriptJSON::from(&transcript)).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["participantIds"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            value["transcripts"][0]["witness"]["potPubkeys"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let transcript_json: TranscriptJSON = serde_json::from_str(&json).unwrap();
+        assert_eq!(Transcript::try_from(&transcript_json).unwrap(), transcript);
+    }
+
+    #[test]
+    fn transcript_json_errors() {
+        use crate::error::{DeserialiseError, Group, PointDeserialiseError};
+
+        let (transcript, _, _) = update_transcript_with_secrets(
+            &CeremonyConfig {
+                ceremonies: vec![Parameters::new(8, 2); 4],
+                ..CeremonyConfig::default()
+            },
+            Transcript::new(&[Parameters::new(8, 2); 4]).unwrap(),
+            vec!["0x01".to_string(); 4],
+        )
+        .unwrap();
+        let json = serde_json::to_value(TranscriptJSON::from(&transcript)).unwrap();
+
+        // A corrupted point is reported with its ceremony, rather than decoding as the starting transcript
+        let mut bad_json = json.clone();
+        bad_json["transcripts"][2]["powersOfTau"]["G1Powers"][5] = "0x1234".into();
+        let transcript_json: TranscriptJSON = serde_json::from_value(bad_json).unwrap();
+        assert_eq!(
+            Transcript::try_from(&transcript_json),
+            Err(BatchDeserialiseError::InvalidSRS {
+                ceremony: 2,
+                error: DeserialiseError::InvalidPoint {
+                    group: Group::G1,
+                    index: 5,
+                    error: PointDeserialiseError::InvalidLength {
+                        expected: 96,
+                        got: 2
+                    }
+                }
+            })
+        );
+
+        let mut bad_json = json.clone();
+        bad_json["transcripts"][1]["witness"]["potPubkeys"][1] = "0xzz".into();
+        let transcript_json: TranscriptJSON = serde_json::from_value(bad_json).unwrap();
+        assert_eq!(
+            Transcript::try_from(&transcript_json),
+            Err(BatchDeserialiseError::InvalidWitness {
+                ceremony: 1,
+                error: DeserialiseError::InvalidPoint {
+                    group: Group::G2,
+                    index: 1,
+                    error: PointDeserialiseError::InvalidHex
+                }
+            })
+        );
+
+        let mut bad_json = json;
+        bad_json["transcripts"][3]
+            .as_object_mut()
+            .unwrap()
+            .remove("witness");
+        let transcript_json: TranscriptJSON = serde_json::from_value(bad_json).unwrap();
+        assert_eq!(
+            Transcript::try_from(&transcript_json),
+            Err(BatchDeserialiseError::MissingWitness { ceremony: 3 })
+        );
     }
 }
@@ -0,0 +1,582 @@
+// The messages exchanged between a contributor and the coordinator, and the transports that
+// carry them.
+//
+// A contributor asks for the state of the ceremony, and is either told where they are in the
+// queue or given the transcript and the time they have left. They then submit their updated
+// transcript and update proofs, and receive either a signed receipt or the reason it was rejected.
+//
+// Each message is sent as JSON, using `TranscriptJSON` for transcripts and
+// `UpdateProof::serialise` for update proofs, prefixed with its length as a big endian u64.
+use std::{
+    io::{Read, Write},
+    sync::mpsc::{channel, Receiver, Sender},
+    time::Duration,
+};
+
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    sdk::{
+        coordinator::{Clock, ContributionSignatures, Coordinator, Turn},
+        receipt::{InclusionReceipt, InclusionReceiptJSON},
+        transcript::{Transcript, TranscriptJSON},
+        TransportError,
+    },
+    serialisation::{deserialise_identity_signature, serialise_identity_signature},
+    update_proof::UpdateProof,
+};
+
+// The largest message that will be read, which is enough for a transcript of the default
+// ceremonies with a long history
+pub const MAX_MESSAGE_SIZE: u64 = 1 << 30;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    // Sent by a contributor to join the queue, or to find out whether it is their turn
+    RequestState {
+        participant_id: String,
+    },
+    // Sent by a contributor with their updated transcript, an update proof for each ceremony
+    // and any signatures they made
+    SubmitContribution {
+        participant_id: String,
+        transcript: Transcript,
+        update_proofs: Vec<UpdateProof>,
+        signatures: ContributionSignatures,
+    },
+    // Sent by the coordinator when it is not yet the contributor's turn
+    Waiting {
+        position: usize,
+    },
+    // Sent by the coordinator when it is the contributor's turn.
+    // The time remaining is sent with millisecond precision.
+    Turn(Turn),
+    // Sent by the coordinator when it accepts a contribution, with the receipt it signed
+    Receipt(InclusionReceipt),
+    // Sent by the coordinator when it does not accept a request or a contribution
+    Rejection {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MessageJSON {
+    RequestState {
+        #[serde(rename = "participantId")]
+        participant_id: String,
+    },
+    SubmitContribution {
+        #[serde(rename = "participantId")]
+        participant_id: String,
+        transcript: TranscriptJSON,
+        #[serde(rename = "updateProofs")]
+        update_proofs: Vec<[String; 2]>,
+        // As in the witness, a missing signature is an empty string
+        #[serde(rename = "blsSignatures")]
+        bls_signatures: Vec<String>,
+        #[serde(
+            rename = "ecdsaSignature",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        ecdsa_signature: Option<String>,
+    },
+    Waiting {
+        position: usize,
+    },
+    Turn {
+        #[serde(rename = "participantId")]
+        participant_id: String,
+        transcript: TranscriptJSON,
+        #[serde(rename = "timeRemainingMillis")]
+        time_remaining_millis: u64,
+    },
+    Receipt(InclusionReceiptJSON),
+    Rejection {
+        reason: String,
+    },
+}
+
+impl From<&Message> for MessageJSON {
+    fn from(message: &Message) -> Self {
+        match message {
+            Message::RequestState { participant_id } => MessageJSON::RequestState {
+                participant_id: participant_id.clone(),
+            },
+            Message::SubmitContribution {
+                participant_id,
+                transcript,
+                update_proofs,
+                signatures,
+            } => MessageJSON::SubmitContribution {
+                participant_id: participant_id.clone(),
+                transcript: TranscriptJSON::from(transcript),
+                update_proofs: update_proofs.iter().map(UpdateProof::serialise).collect(),
+                bls_signatures: signatures
+                    .bls_signatures
+                    .iter()
+                    .map(|signature| {
+                        signature
+                            .as_ref()
+                            .map(serialise_identity_signature)
+                            .unwrap_or_default()
+                    })
+                    .collect(),
+                ecdsa_signature: signatures.ecdsa_signature.clone(),
+            },
+            Message::Waiting { position } => MessageJSON::Waiting {
+                position: *position,
+            },
+            Message::Turn(turn) => MessageJSON::Turn {
+                participant_id: turn.participant_id.clone(),
+                transcript: TranscriptJSON::from(&turn.transcript),
+                time_remaining_millis: turn.time_remaining.as_millis() as u64,
+            },
+            Message::Receipt(receipt) => MessageJSON::Receipt(InclusionReceiptJSON::from(receipt)),
+            Message::Rejection { reason } => MessageJSON::Rejection {
+                reason: reason.clone(),
+            },
+        }
+    }
+}
+
+impl TryFrom<MessageJSON> for Message {
+    type Error = TransportError;
+
+    fn try_from(message_json: MessageJSON) -> Result<Self, Self::Error> {
+        let message = match message_json {
+            MessageJSON::RequestState { participant_id } => {
+                Message::RequestState { participant_id }
+            }
+            MessageJSON::SubmitContribution {
+                participant_id,
+                transcript,
+                update_proofs,
+                bls_signatures,
+                ecdsa_signature,
+            } => Message::SubmitContribution {
+                participant_id,
+                transcript: decode_transcript(&transcript)?,
+                update_proofs: update_proofs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, update_proof)| {
+                        UpdateProof::deserialise(update_proof)
+                            .map_err(|error| TransportError::InvalidUpdateProof { index, error })
+                    })
+                    .collect::<Result<_, _>>()?,
+                signatures: ContributionSignatures {
+                    bls_signatures: bls_signatures
+                        .iter()
+                        .enumerate()
+                        .map(|(index, signature)| {
+                            if signature.is_empty() {
+                                return Ok(None);
+                            }
+                            deserialise_identity_signature(signature)
+                                .map(Some)
+                                .map_err(|error| TransportError::InvalidIdentitySignature {
+                                    index,
+                                    error,
+                                })
+                        })
+                        .collect::<Result<_, _>>()?,
+                    ecdsa_signature,
+                },
+            },
+            MessageJSON::Waiting { position } => Message::Waiting { position },
+            MessageJSON::Turn {
+                participant_id,
+                transcript,
+                time_remaining_millis,
+            } => Message::Turn(Turn {
+                participant_id,
+                transcript: decode_transcript(&transcript)?,
+                time_remaining: Duration::from_millis(time_remaining_millis),
+            }),
+            MessageJSON::Receipt(receipt_json) => Message::Receipt(
+                InclusionReceipt::try_from(&receipt_json)
+                    .map_err(TransportError::InvalidReceipt)?,
+            ),
+            MessageJSON::Rejection { reason } => Message::Rejection { reason },
+        };
+        Ok(message)
+    }
+}
+
+fn decode_transcript(transcript_json: &TranscriptJSON) -> Result<Transcript, TransportError> {
+    Transcript::try_from(transcript_json).map_err(TransportError::InvalidTranscript)
+}
+
+impl Message {
+    // Encodes the message as JSON, without the length prefix
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&MessageJSON::from(self)).expect("messages can always be serialised")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Message, TransportError> {
+        let message_json: MessageJSON =
+            serde_json::from_slice(bytes).map_err(|_| TransportError::InvalidJson)?;
+        Message::try_from(message_json)
+    }
+}
+
+// A connection between a contributor and the coordinator
+pub trait CeremonyTransport {
+    fn send(&mut self, message: &Message) -> Result<(), TransportError>;
+
+    // Blocks until a message arrives, or the other end disconnects
+    fn receive(&mut self) -> Result<Message, TransportError>;
+
+    // Sends a message and waits for the reply
+    fn request(&mut self, message: &Message) -> Result<Message, TransportError> {
+        self.send(message)?;
+        self.receive()
+    }
+}
+
+// Writes a message with its length prefix
+pub fn write_message<W: Write>(writer: &mut W, message: &Message) -> Result<(), TransportError> {
+    let bytes = message.to_bytes();
+    writer.write_all(&(bytes.len() as u64).to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// Reads a message with its length prefix, the length is checked before anything is read.
+// The buffer grows as bytes arrive, so a peer cannot make us allocate more than it sends.
+pub fn read_message<R: Read>(reader: &mut R) -> Result<Message, TransportError> {
+    let mut length = [0u8; 8];
+    reader.read_exact(&mut length)?;
+    let length = u64::from_be_bytes(length);
+    if length > MAX_MESSAGE_SIZE {
+        return Err(TransportError::MessageTooLarge {
+            max: MAX_MESSAGE_SIZE,
+            got: length,
+        });
+    }
+
+    let mut bytes = Vec::new();
+    reader.take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        return Err(TransportError::Disconnected);
+    }
+    Message::from_bytes(&bytes)
+}
+
+// One end of an in-memory connection, for running a contributor and the coordinator in one process.
+// Messages are encoded in the same way as over a socket.
+pub struct MemoryTransport {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+}
+
+// Creates both ends of an in-memory connection
+pub fn memory_channel() -> (MemoryTransport, MemoryTransport) {
+    let (a_sender, b_receiver) = channel();
+    let (b_sender, a_receiver) = channel();
+    (
+        MemoryTransport {
+            sender: a_sender,
+            receiver: a_receiver,
+        },
+        MemoryTransport {
+            sender: b_sender,
+            receiver: b_receiver,
+        },
+    )
+}
+
+impl CeremonyTransport for MemoryTransport {
+    fn send(&mut self, message: &Message) -> Result<(), TransportError> {
+        self.sender
+            .send(message.to_bytes())
+            .map_err(|_| TransportError::Disconnected)
+    }
+
+    fn receive(&mut self) -> Result<Message, TransportError> {
+        let bytes = self
+            .receiver
+            .recv()
+            .map_err(|_| TransportError::Disconnected)?;
+        Message::from_bytes(&bytes)
+    }
+}
+
+// A connection over a Unix domain socket
+#[cfg(unix)]
+pub struct UnixSocketTransport {
+    stream: UnixStream,
+}
+
+#[cfg(unix)]
+impl UnixSocketTransport {
+    // Connects to a coordinator that is listening on the socket at `path`
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self, TransportError> {
+        Ok(UnixSocketTransport::from_stream(UnixStream::connect(path)?))
+    }
+
+    // Wraps a stream, such as one accepted by a `UnixListener`
+    pub fn from_stream(stream: UnixStream) -> Self {
+        UnixSocketTransport { stream }
+    }
+}
+
+#[cfg(unix)]
+impl CeremonyTransport for UnixSocketTransport {
+    fn send(&mut self, message: &Message) -> Result<(), TransportError> {
+        write_message(&mut self.stream, message)
+    }
+
+    fn receive(&mut self) -> Result<Message, TransportError> {
+        read_message(&mut self.stream)
+    }
+}
+
+// The coordinator's reply to a message from a contributor
+pub fn handle_message<C: Clock>(coordinator: &mut Coordinator<C>, message: Message) -> Message {
+    match message {
+        Message::RequestState { participant_id } => {
+            if let Some(turn) = coordinator.current_turn(&participant_id) {
+                return Message::Turn(turn);
+            }
+            coordinator.join(participant_id.clone());
+            // The turn may go to another participant, who will be told when they next ask
+            if let Some(turn) = coordinator.next_turn() {
+                if turn.participant_id == participant_id {
+                    return Message::Turn(turn);
+                }
+            }
+            Message::Waiting {
+                position: coordinator
+                    .queue_position(&participant_id)
+                    .unwrap_or_default(),
+            }
+        }
+        Message::SubmitContribution {
+            participant_id,
+            transcript,
+            update_proofs,
+            signatures,
+        } => match coordinator.submit(&participant_id, transcript, update_proofs, signatures) {
+            Ok(receipt) => Message::Receipt(receipt),
+            Err(error) => Message::Rejection {
+                reason: error.to_string(),
+            },
+        },
+        _ => Message::Rejection {
+            reason: "the coordinator only accepts requests for state and contributions".to_string(),
+        },
+    }
+}
+
+// Replies to every message on the connection until the contributor disconnects
+pub fn serve<C: Clock, T: CeremonyTransport>(
+    coordinator: &mut Coordinator<C>,
+    transport: &mut T,
+) -> Result<(), TransportError> {
+    loop {
+        let message = match transport.receive() {
+            Ok(message) => message,
+            Err(TransportError::Disconnected) => return Ok(()),
+            // The contributor is told why their message could not be decoded
+            Err(error) => Message::Rejection {
+                reason: error.to_string(),
+            },
+        };
+        let reply = match message {
+            Message::Rejection { .. } => message,
+            message => handle_message(coordinator, message),
+        };
+        transport.send(&reply)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keypair::PrivateKey,
+        sdk::{
+            coordinator::SystemClock,
+            receipt::CoordinatorKey,
+            test_utils::{test_config, test_secrets},
+            transcript::update_transcript_with_secrets,
+        },
+    };
+    use std::thread;
+
+    fn request_turn<T: CeremonyTransport>(transport: &mut T, participant_id: &str) -> Turn {
+        let state = transport
+            .request(&Message::RequestState {
+                participant_id: participant_id.to_string(),
+            })
+            .unwrap();
+        match state {
+            Message::Turn(turn) => turn,
+            message => panic!("expected a turn, got {:?}", message),
+        }
+    }
+
+    // Checks that the reply is a receipt for the contribution at the given position
+    fn assert_receipt(reply: Message, participant_id: &str, position: usize) {
+        match reply {
+            Message::Receipt(receipt) => {
+                assert!(receipt.verify(&CoordinatorKey::from_u64(42).public_key()));
+                assert_eq!(receipt.participant_id, participant_id);
+                assert_eq!(receipt.position, position);
+            }
+            message => panic!("expected a receipt, got {:?}", message),
+        }
+    }
+
+    fn contribute<T: CeremonyTransport>(
+        transport: &mut T,
+        participant_id: &str,
+        transcript: Transcript,
+    ) -> Message {
+        let config = test_config();
+        let (transcript, update_proofs, _) =
+            update_transcript_with_secrets(&config, transcript, test_secrets()).unwrap();
+        // The participant signs their identity in the first ceremony only
+        let signatures = ContributionSignatures {
+            bls_signatures: vec![
+                Some(PrivateKey::from_u64(0x1234).sign_identity(participant_id)),
+                None,
+            ],
+            ecdsa_signature: None,
+        };
+        transport
+            .request(&Message::SubmitContribution {
+                participant_id: participant_id.to_string(),
+                transcript,
+                update_proofs,
+                signatures,
+            })
+            .unwrap()
+    }
+
+    // Runs the coordinator on its own thread, serving each connection in turn, and has Alice and
+    // then Bob contribute from this thread
+    fn full_exchange<T, S>(connections: S, alice: impl FnOnce() -> T, bob: impl FnOnce() -> T)
+    where
+        T: CeremonyTransport + Send + 'static,
+        S: Iterator<Item = T> + Send + 'static,
+    {
+        let config = test_config();
+        let transcript = Transcript::with_config(&config).unwrap();
+        let coordinator = thread::spawn(move || {
+            let mut coordinator = Coordinator::new(
+                config,
+                transcript,
+                CoordinatorKey::from_u64(42),
+                SystemClock,
+                Duration::from_secs(600),
+            )
+            .unwrap();
+            for mut connection in connections {
+                serve(&mut coordinator, &mut connection).unwrap();
+            }
+            coordinator
+        });
+
+        let mut transport = alice();
+        let turn = request_turn(&mut transport, "alice");
+        assert_eq!(turn.participant_id, "alice");
+        assert_receipt(
+            contribute(&mut transport, "alice", turn.transcript),
+            "alice",
+            1,
+        );
+        drop(transport);
+
+        let mut transport = bob();
+        // Bob contributes to the starting transcript rather than the one he was given
+        let turn = request_turn(&mut transport, "bob");
+        assert_eq!(turn.transcript.participant_ids.len(), 2);
+        let starting_transcript = Transcript::with_config(&test_config()).unwrap();
+        match contribute(&mut transport, "bob", starting_transcript) {
+            Message::Rejection { reason } => assert!(reason.starts_with("invalid contribution")),
+            message => panic!("expected a rejection, got {:?}", message),
+        }
+        // His turn is over, but he can join the queue again
+        let turn = request_turn(&mut transport, "bob");
+        assert_receipt(contribute(&mut transport, "bob", turn.transcript), "bob", 2);
+        drop(transport);
+
+        let coordinator = coordinator.join().unwrap();
+        assert_eq!(coordinator.history().len(), 2);
+        assert_eq!(
+            coordinator.transcript().participant_ids[1..],
+            ["alice", "bob"]
+        );
+        let witnesses = &coordinator.transcript().witnesses;
+        assert!(witnesses[0].bls_signatures[1..].iter().all(Option::is_some));
+        assert!(witnesses[1].bls_signatures[1..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn memory_transport_exchange() {
+        let (alice, alice_server) = memory_channel();
+        let (bob, bob_server) = memory_channel();
+        full_exchange(
+            vec![alice_server, bob_server].into_iter(),
+            move || alice,
+            move || bob,
+        );
+
+        // A message of an unknown type is rejected without reaching the coordinator
+        assert_eq!(
+            Message::from_bytes(b"{\"type\":\"unknown\"}"),
+            Err(TransportError::InvalidJson)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_transport_exchange() {
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!(
+            "small-powers-of-tau-transport-{}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let connections = (0..2).map(move |_| {
+            let (stream, _) = listener.accept().unwrap();
+            UnixSocketTransport::from_stream(stream)
+        });
+        full_exchange(
+            connections,
+            || UnixSocketTransport::connect(&path).unwrap(),
+            || UnixSocketTransport::connect(&path).unwrap(),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        // The length of a message is checked before it is read
+        let mut bytes = (MAX_MESSAGE_SIZE + 1).to_be_bytes().to_vec();
+        bytes.extend_from_slice(b"{}");
+        assert_eq!(
+            read_message(&mut bytes.as_slice()),
+            Err(TransportError::MessageTooLarge {
+                max: MAX_MESSAGE_SIZE,
+                got: MAX_MESSAGE_SIZE + 1
+            })
+        );
+
+        // A message that is shorter than its length prefix means the other end went away
+        let mut bytes = 1024u64.to_be_bytes().to_vec();
+        bytes.extend_from_slice(b"{}");
+        assert_eq!(
+            read_message(&mut bytes.as_slice()),
+            Err(TransportError::Disconnected)
+        );
+    }
+}
@@ -0,0 +1,116 @@
+// Buffers for values derived from a contributors secret, such as the powers of tau.
+//
+// The buffer is wiped when it is dropped. With the `mlock` feature on Linux, its pages are
+// also locked into RAM while it is alive, so that the secrets are never written to swap.
+use std::ops::Deref;
+
+use zeroize::Zeroize;
+
+pub(crate) struct SecretVec<T: Zeroize> {
+    values: Vec<T>,
+    // Whether the pages of `values` were locked, locking is best effort and can fail
+    // if the process is over its RLIMIT_MEMLOCK
+    #[cfg(all(feature = "mlock", target_os = "linux"))]
+    locked: bool,
+}
+
+impl<T: Zeroize> SecretVec<T> {
+    // Allocates space for `capacity` values up front, so that the buffer is never
+    // reallocated and no unwiped copies are left behind
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let values = Vec::with_capacity(capacity);
+        #[cfg(all(feature = "mlock", target_os = "linux"))]
+        let locked = mlock::lock(&values);
+
+        SecretVec {
+            values,
+            #[cfg(all(feature = "mlock", target_os = "linux"))]
+            locked,
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: T) {
+        assert!(
+            self.values.len() < self.values.capacity(),
+            "a secret buffer must not be reallocated"
+        );
+        self.values.push(value);
+    }
+
+    // Wipes every value, and the spare capacity of the buffer
+    pub(crate) fn wipe(&mut self) {
+        self.values.zeroize();
+    }
+}
+
+impl<T: Zeroize> Deref for SecretVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.values
+    }
+}
+
+impl<T: Zeroize> Drop for SecretVec<T> {
+    fn drop(&mut self) {
+        self.wipe();
+        #[cfg(all(feature = "mlock", target_os = "linux"))]
+        if self.locked {
+            mlock::unlock(&self.values);
+        }
+    }
+}
+
+#[cfg(all(feature = "mlock", target_os = "linux"))]
+mod mlock {
+    // Locks the allocated capacity of the vector into RAM
+    pub(super) fn lock<T>(values: &Vec<T>) -> bool {
+        let size = values.capacity() * std::mem::size_of::<T>();
+        if size == 0 {
+            return false;
+        }
+        // Safety: the pointer and size describe the allocation owned by `values`
+        unsafe { libc::mlock(values.as_ptr() as *const libc::c_void, size) == 0 }
+    }
+
+    // Locks do not nest, so this also unlocks any page that is shared with another locked buffer
+    pub(super) fn unlock<T>(values: &Vec<T>) {
+        let size = values.capacity() * std::mem::size_of::<T>();
+        // Safety: the pointer and size describe the allocation owned by `values`
+        unsafe {
+            libc::munlock(values.as_ptr() as *const libc::c_void, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn secret_vec_is_wiped() {
+        let mut values = SecretVec::with_capacity(4);
+        for i in 1..=4u64 {
+            values.push(Fr::from(i));
+        }
+        assert_eq!(values[3], Fr::from(4u64));
+
+        let ptr = values.values.as_ptr() as *const u8;
+        let size = values.values.capacity() * std::mem::size_of::<Fr>();
+        values.wipe();
+        assert!(values.is_empty());
+
+        // The allocation is still owned by `values`, so it can be read until it is dropped
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, size) };
+        assert!(bytes.iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be reallocated")]
+    fn secret_vec_does_not_grow() {
+        let mut values = SecretVec::with_capacity(1);
+        values.push(Fr::from(1u64));
+        values.push(Fr::from(2u64));
+    }
+}
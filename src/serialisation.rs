@@ -1,108 +1,176 @@
+pub use crate::interop_point_encoding::PointEncoding;
 use crate::interop_point_encoding::{
-    deserialize_g1, deserialize_g2, serialize_g1, serialize_g2, G1_SERIALISED_SIZE,
-    G2_SERIALISED_SIZE,
+    deserialize_g1, deserialize_g1_uncompressed, deserialize_g2, deserialize_g2_uncompressed,
+    serialize_g1, serialize_g1_uncompressed, serialize_g2, serialize_g2_uncompressed,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    error::{DeserialiseError, Group, PointDeserialiseError},
+    keypair::PublicKey,
     srs::{Parameters, SRS},
     update_proof::UpdateProof,
+    witness::Witness,
 };
-use ark_bls12_381::{G1Projective, G2Projective};
+use ark_bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective};
 use ark_ec::{AffineCurve, ProjectiveCurve};
 
-fn hex_string_to_g1(hex_str: &str) -> Option<G1Projective> {
-    if let Some(stripped_point_json) = hex_str.strip_prefix("0x") {
-        let bytes = hex::decode(stripped_point_json).ok()?;
-        if bytes.len() != G1_SERIALISED_SIZE {
-            return None;
-        }
-        let mut fixed_array = [0u8; G1_SERIALISED_SIZE];
-        fixed_array.copy_from_slice(&bytes);
-        return Some(deserialize_g1(fixed_array)?.into_projective());
-    } else {
-        return None;
-    }
-}
-fn hex_string_to_g2(hex_str: &str) -> Option<G2Projective> {
-    if let Some(stripped_point_json) = hex_str.strip_prefix("0x") {
-        let bytes = hex::decode(stripped_point_json).ok()?;
-        if bytes.len() != G2_SERIALISED_SIZE {
-            return None;
-        }
-        let mut fixed_array = [0u8; G2_SERIALISED_SIZE];
-        fixed_array.copy_from_slice(&bytes);
-        return Some(deserialize_g2(fixed_array)?.into_projective());
-    } else {
-        return None;
+fn hex_string_to_bytes(hex_str: &str) -> Result<Vec<u8>, PointDeserialiseError> {
+    let stripped_point_json = hex_str
+        .strip_prefix("0x")
+        .ok_or(PointDeserialiseError::MissingHexPrefix)?;
+    hex::decode(stripped_point_json).map_err(|_| PointDeserialiseError::InvalidHex)
+}
+fn bytes_to_fixed_array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], PointDeserialiseError> {
+    bytes
+        .try_into()
+        .map_err(|_| PointDeserialiseError::InvalidLength {
+            expected: N,
+            got: bytes.len(),
+        })
+}
+// The encoding of a point is read from its compression flag, so that
+// both compressed and uncompressed points can be deserialised
+fn encoding_of(bytes: &[u8]) -> PointEncoding {
+    match bytes.first() {
+        Some(byte) if byte >> 7 == 0 => PointEncoding::Uncompressed,
+        _ => PointEncoding::Compressed,
     }
 }
+fn hex_string_to_g1(hex_str: &str) -> Result<G1Projective, PointDeserialiseError> {
+    let bytes = hex_string_to_bytes(hex_str)?;
+    let point = match encoding_of(&bytes) {
+        PointEncoding::Compressed => deserialize_g1(bytes_to_fixed_array(&bytes)?)?,
+        PointEncoding::Uncompressed => deserialize_g1_uncompressed(bytes_to_fixed_array(&bytes)?)?,
+    };
+    Ok(point.into_projective())
+}
+fn hex_string_to_g2(hex_str: &str) -> Result<G2Projective, PointDeserialiseError> {
+    let bytes = hex_string_to_bytes(hex_str)?;
+    let point = match encoding_of(&bytes) {
+        PointEncoding::Compressed => deserialize_g2(bytes_to_fixed_array(&bytes)?)?,
+        PointEncoding::Uncompressed => deserialize_g2_uncompressed(bytes_to_fixed_array(&bytes)?)?,
+    };
+    Ok(point.into_projective())
+}
+fn g1_to_hex_string(point: &G1Affine, encoding: PointEncoding) -> String {
+    let point_as_hex = match encoding {
+        PointEncoding::Compressed => hex::encode(serialize_g1(point)),
+        PointEncoding::Uncompressed => hex::encode(serialize_g1_uncompressed(point)),
+    };
+    format!("0x{}", point_as_hex)
+}
+pub(crate) fn g2_to_hex_string(point: &G2Affine, encoding: PointEncoding) -> String {
+    let point_as_hex = match encoding {
+        PointEncoding::Compressed => hex::encode(serialize_g2(point)),
+        PointEncoding::Uncompressed => hex::encode(serialize_g2_uncompressed(point)),
+    };
+    format!("0x{}", point_as_hex)
+}
 
 impl SRS {
     pub fn serialise(&self) -> (Vec<String>, Vec<String>) {
-        self.to_json_array()
+        self.to_json_array(PointEncoding::Compressed)
     }
 
-    fn g1s_to_json_array(g1s: &[G1Projective]) -> Vec<String> {
-        let mut g1_points_json = Vec::new();
+    // Serialises the SRS using the given point encoding.
+    // `deserialise` accepts either encoding.
+    pub fn serialise_with_encoding(&self, encoding: PointEncoding) -> (Vec<String>, Vec<String>) {
+        self.to_json_array(encoding)
+    }
 
+    fn g1s_to_json_array(g1s: &[G1Projective], encoding: PointEncoding) -> Vec<String> {
         let g1_points_affine = G1Projective::batch_normalization_into_affine(g1s);
 
-        for point in &g1_points_affine {
-            let mut point_as_hex = hex::encode(serialize_g1(point));
-            point_as_hex.insert_str(0, "0x");
-            g1_points_json.push(point_as_hex)
-        }
-
-        g1_points_json
+        g1_points_affine
+            .iter()
+            .map(|point| g1_to_hex_string(point, encoding))
+            .collect()
     }
-    fn g2s_to_json_array(g2s: &[G2Projective]) -> Vec<String> {
-        let mut g2_points_json = Vec::new();
-
+    fn g2s_to_json_array(g2s: &[G2Projective], encoding: PointEncoding) -> Vec<String> {
         let g2_points_affine = G2Projective::batch_normalization_into_affine(g2s);
 
-        for point in &g2_points_affine {
-            let mut point_as_hex = hex::encode(serialize_g2(point));
-            point_as_hex.insert_str(0, "0x");
-            g2_points_json.push(point_as_hex)
-        }
-
-        g2_points_json
+        g2_points_affine
+            .iter()
+            .map(|point| g2_to_hex_string(point, encoding))
+            .collect()
     }
 
-    fn to_json_array(&self) -> (Vec<String>, Vec<String>) {
-        let g1_points_json = Self::g1s_to_json_array(self.g1_elements());
-        let g2_points_json = Self::g2s_to_json_array(self.g2_elements());
+    fn to_json_array(&self, encoding: PointEncoding) -> (Vec<String>, Vec<String>) {
+        let g1_points_json = Self::g1s_to_json_array(self.g1_elements(), encoding);
+        let g2_points_json = Self::g2s_to_json_array(self.g2_elements(), encoding);
 
         (g1_points_json, g2_points_json)
     }
 
     // We do not check if the point is the identity when deserialising
     // What we do check, is that every point is a point on the curve
-    pub fn deserialise(json_arr: (&[String], &[String]), parameters: Parameters) -> Option<Self> {
+    pub fn deserialise(
+        json_arr: (&[String], &[String]),
+        parameters: Parameters,
+    ) -> Result<Self, DeserialiseError> {
         SRS::from_json_array(json_arr, parameters)
     }
 
-    fn from_json_array(json_array: (&[String], &[String]), parameters: Parameters) -> Option<Self> {
+    fn from_json_array(
+        json_array: (&[String], &[String]),
+        parameters: Parameters,
+    ) -> Result<Self, DeserialiseError> {
         let (g1_points_json_array, g2_points_json_array) = json_array;
-        let mut g1 = vec![];
-        let mut g2 = vec![];
 
-        for point_json in g1_points_json_array {
-            g1.push(hex_string_to_g1(&point_json)?);
+        // Check the number of points before decoding any of them
+        if parameters.num_g1_elements_needed < 2 {
+            return Err(DeserialiseError::NotEnoughPowers {
+                group: Group::G1,
+                got: parameters.num_g1_elements_needed,
+            });
+        }
+        if parameters.num_g2_elements_needed < 2 {
+            return Err(DeserialiseError::NotEnoughPowers {
+                group: Group::G2,
+                got: parameters.num_g2_elements_needed,
+            });
+        }
+        if g1_points_json_array.len() != parameters.num_g1_elements_needed {
+            return Err(DeserialiseError::WrongNumberOfPowers {
+                group: Group::G1,
+                expected: parameters.num_g1_elements_needed,
+                got: g1_points_json_array.len(),
+            });
         }
-        for point_json in g2_points_json_array {
-            g2.push(hex_string_to_g2(&point_json)?)
+        if g2_points_json_array.len() != parameters.num_g2_elements_needed {
+            return Err(DeserialiseError::WrongNumberOfPowers {
+                group: Group::G2,
+                expected: parameters.num_g2_elements_needed,
+                got: g2_points_json_array.len(),
+            });
         }
 
-        if g1.len() != parameters.num_g1_elements_needed {
-            return None;
+        let mut g1 = Vec::with_capacity(g1_points_json_array.len());
+        let mut g2 = Vec::with_capacity(g2_points_json_array.len());
+
+        for (index, point_json) in g1_points_json_array.iter().enumerate() {
+            let point =
+                hex_string_to_g1(point_json).map_err(|error| DeserialiseError::InvalidPoint {
+                    group: Group::G1,
+                    index,
+                    error,
+                })?;
+            g1.push(point);
         }
-        if g2.len() != parameters.num_g2_elements_needed {
-            return None;
+        for (index, point_json) in g2_points_json_array.iter().enumerate() {
+            let point =
+                hex_string_to_g2(point_json).map_err(|error| DeserialiseError::InvalidPoint {
+                    group: Group::G2,
+                    index,
+                    error,
+                })?;
+            g2.push(point)
         }
 
-        SRS::from_vectors(g1, g2)
+        // `from_vectors` only fails when there are less than two powers in a group
+        // which was checked above
+        Ok(SRS::from_vectors(g1, g2).expect("srs has at least two powers in each group"))
     }
 }
 
@@ -111,30 +179,69 @@ impl UpdateProof {
         self.to_json_array()
     }
 
-    fn to_json_array(&self) -> [String; 2] {
-        let mut a = hex::encode(serialize_g2(&self.commitment_to_secret.into_affine()));
-        a.insert_str(0, "0x");
-
-        let mut b = hex::encode(serialize_g1(&self.new_accumulated_point.into_affine()));
-        b.insert_str(0, "0x");
+    fn to_json_array(self) -> [String; 2] {
+        let a = g2_to_hex_string(
+            &self.commitment_to_secret.into_affine(),
+            PointEncoding::Compressed,
+        );
+        let b = g1_to_hex_string(
+            &self.new_accumulated_point.into_affine(),
+            PointEncoding::Compressed,
+        );
 
         [a, b]
     }
-    pub fn deserialise(json_array: [String; 2]) -> Option<Self> {
+    pub fn deserialise(json_array: [String; 2]) -> Result<Self, DeserialiseError> {
         UpdateProof::from_json_array(json_array)
     }
 
-    fn from_json_array(points_json_arr: [String; 2]) -> Option<Self> {
-        let commitment_to_secret = hex_string_to_g2(&points_json_arr[0])?;
-        let new_accumulated_point = hex_string_to_g1(&points_json_arr[1])?;
-
-        Some(UpdateProof {
+    // An update proof holds a single point in each group, so the index is always zero
+    fn from_json_array(points_json_arr: [String; 2]) -> Result<Self, DeserialiseError> {
+        let commitment_to_secret = hex_string_to_g2(&points_json_arr[0]).map_err(|error| {
+            DeserialiseError::InvalidPoint {
+                group: Group::G2,
+                index: 0,
+                error,
+            }
+        })?;
+        let new_accumulated_point = hex_string_to_g1(&points_json_arr[1]).map_err(|error| {
+            DeserialiseError::InvalidPoint {
+                group: Group::G1,
+                index: 0,
+                error,
+            }
+        })?;
+
+        Ok(UpdateProof {
             commitment_to_secret,
             new_accumulated_point,
         })
     }
 }
 
+impl PublicKey {
+    // Serialises the public key as a 0x prefixed hex string of its compressed encoding
+    pub fn to_hex(&self) -> String {
+        g2_to_hex_string(&self.point().into_affine(), PointEncoding::Compressed)
+    }
+
+    // Deserialises a public key, checking that it is in the prime order subgroup
+    pub fn from_hex(hex_str: &str) -> Result<PublicKey, PointDeserialiseError> {
+        let point = hex_string_to_g2(hex_str)?;
+        PublicKey::new(point).ok_or(PointDeserialiseError::NotInSubgroup)
+    }
+}
+
+// Identity signatures are G1 points, and so use the same compressed encoding as the SRS
+pub fn serialise_identity_signature(signature: &G1Projective) -> String {
+    g1_to_hex_string(&signature.into_affine(), PointEncoding::Compressed)
+}
+pub fn deserialise_identity_signature(
+    hex_str: &str,
+) -> Result<G1Projective, PointDeserialiseError> {
+    hex_string_to_g1(hex_str)
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SRSJson {
     #[serde(rename = "numG1Powers")]
@@ -143,8 +250,23 @@ pub struct SRSJson {
     num_g2_powers: usize,
     #[serde(rename = "powersOfTau")]
     powers_of_tau: PowerOfTau,
-    #[serde(rename = "potPubkey")]
-    pot_pubkey: String,
+    // Only present when the SRS is part of a contribution
+    #[serde(rename = "potPubkey", default, skip_serializing_if = "Option::is_none")]
+    pot_pubkey: Option<String>,
+    // Only present when the SRS is part of a transcript
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    witness: Option<WitnessJson>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WitnessJson {
+    #[serde(rename = "runningProducts")]
+    running_products: Vec<String>,
+    #[serde(rename = "potPubkeys")]
+    pot_pubkeys: Vec<String>,
+    // A contributor that did not sign their identity has an empty string
+    #[serde(rename = "blsSignatures")]
+    bls_signatures: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -155,8 +277,9 @@ pub struct PowerOfTau {
     g2_powers: Vec<String>,
 }
 
-impl From<&SRS> for SRSJson {
-    fn from(srs: &SRS) -> Self {
+impl SRSJson {
+    // Converts the SRS into its JSON form, serialising the powers of tau with the given encoding
+    pub fn with_encoding(srs: &SRS, encoding: PointEncoding) -> Self {
         let g1s = srs.g1_elements();
         let g2s = srs.g2_elements();
 
@@ -164,27 +287,153 @@ impl From<&SRS> for SRSJson {
             num_g1_powers: g1s.len(),
             num_g2_powers: g2s.len(),
             powers_of_tau: PowerOfTau {
-                g1_powers: SRS::g1s_to_json_array(g1s),
-                g2_powers: SRS::g2s_to_json_array(g2s),
+                g1_powers: SRS::g1s_to_json_array(g1s, encoding),
+                g2_powers: SRS::g2s_to_json_array(g2s, encoding),
             },
-            pot_pubkey: SRS::g2s_to_json_array(g2s).get(1).unwrap().to_string(),
+            pot_pubkey: Some(g2_to_hex_string(
+                &g2s[1].into_affine(),
+                PointEncoding::Compressed,
+            )),
+            witness: None,
+        }
+    }
+
+    // Converts the SRS and its witness into the JSON form used in a transcript
+    pub fn with_witness(srs: &SRS, witness: &Witness) -> Self {
+        SRSJson::with_witness_and_encoding(srs, witness, PointEncoding::Compressed)
+    }
+
+    // Like `with_witness`, but the powers of tau are serialised with the given encoding
+    pub fn with_witness_and_encoding(
+        srs: &SRS,
+        witness: &Witness,
+        encoding: PointEncoding,
+    ) -> Self {
+        let mut srs_json = SRSJson::with_encoding(srs, encoding);
+        srs_json.pot_pubkey = None;
+        srs_json.witness = Some(WitnessJson::from(witness));
+        srs_json
+    }
+
+    pub fn witness(&self) -> Option<&WitnessJson> {
+        self.witness.as_ref()
+    }
+
+    // The number of powers that the SRS claims to have, which is checked against the
+    // points when it is decoded
+    pub fn parameters(&self) -> Parameters {
+        Parameters {
+            num_g1_elements_needed: self.num_g1_powers,
+            num_g2_elements_needed: self.num_g2_powers,
         }
     }
 }
-impl From<&SRSJson> for Option<SRS> {
-    fn from(srs: &SRSJson) -> Self {
-        let parameters = Parameters {
-            num_g1_elements_needed: srs.num_g1_powers,
-            num_g2_elements_needed: srs.num_g2_powers,
-        };
+
+impl From<&SRS> for SRSJson {
+    fn from(srs: &SRS) -> Self {
+        SRSJson::with_encoding(srs, PointEncoding::Compressed)
+    }
+}
+impl TryFrom<&SRSJson> for SRS {
+    type Error = DeserialiseError;
+
+    fn try_from(srs: &SRSJson) -> Result<Self, Self::Error> {
         SRS::deserialise(
             (&srs.powers_of_tau.g1_powers, &srs.powers_of_tau.g2_powers),
-            parameters,
+            srs.parameters(),
         )
     }
 }
+impl From<&Witness> for WitnessJson {
+    fn from(witness: &Witness) -> Self {
+        let running_products_affine =
+            G1Projective::batch_normalization_into_affine(&witness.running_products);
+        let pot_pubkeys_affine =
+            G2Projective::batch_normalization_into_affine(&witness.pot_pubkeys);
+
+        Self {
+            running_products: running_products_affine
+                .iter()
+                .map(|point| g1_to_hex_string(point, PointEncoding::Compressed))
+                .collect(),
+            pot_pubkeys: pot_pubkeys_affine
+                .iter()
+                .map(|point| g2_to_hex_string(point, PointEncoding::Compressed))
+                .collect(),
+            bls_signatures: witness
+                .bls_signatures
+                .iter()
+                .map(|signature| match signature {
+                    Some(signature) => {
+                        g1_to_hex_string(&signature.into_affine(), PointEncoding::Compressed)
+                    }
+                    None => String::new(),
+                })
+                .collect(),
+        }
+    }
+}
+impl TryFrom<&WitnessJson> for Witness {
+    type Error = DeserialiseError;
+
+    fn try_from(witness: &WitnessJson) -> Result<Self, Self::Error> {
+        let running_products_len = witness.running_products.len();
+        if witness.pot_pubkeys.len() != running_products_len
+            || witness.bls_signatures.len() != running_products_len
+        {
+            return Err(DeserialiseError::WitnessLengthMismatch {
+                running_products: running_products_len,
+                pot_pubkeys: witness.pot_pubkeys.len(),
+                bls_signatures: witness.bls_signatures.len(),
+            });
+        }
+
+        let mut running_products = Vec::with_capacity(running_products_len);
+        for (index, point_json) in witness.running_products.iter().enumerate() {
+            let point =
+                hex_string_to_g1(point_json).map_err(|error| DeserialiseError::InvalidPoint {
+                    group: Group::G1,
+                    index,
+                    error,
+                })?;
+            running_products.push(point);
+        }
+        let mut pot_pubkeys = Vec::with_capacity(running_products_len);
+        for (index, point_json) in witness.pot_pubkeys.iter().enumerate() {
+            let point =
+                hex_string_to_g2(point_json).map_err(|error| DeserialiseError::InvalidPoint {
+                    group: Group::G2,
+                    index,
+                    error,
+                })?;
+            pot_pubkeys.push(point);
+        }
+        let mut bls_signatures = Vec::with_capacity(running_products_len);
+        for (index, point_json) in witness.bls_signatures.iter().enumerate() {
+            if point_json.is_empty() {
+                bls_signatures.push(None);
+                continue;
+            }
+            let point =
+                hex_string_to_g1(point_json).map_err(|error| DeserialiseError::InvalidPoint {
+                    group: Group::G1,
+                    index,
+                    error,
+                })?;
+            bls_signatures.push(Some(point));
+        }
+
+        Ok(Witness {
+            running_products,
+            pot_pubkeys,
+            bls_signatures,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::interop_point_encoding::{G1_SERIALISED_SIZE, G2_SERIALISED_SIZE};
     use crate::keypair::PrivateKey;
     use ark_bls12_381::Fr;
     use ark_ec::ProjectiveCurve;
@@ -222,4 +471,127 @@ mod tests {
 
         assert_eq!(acc, deserialised_srs);
     }
+
+    #[test]
+    fn srs_serialise_uncompressed_roundtrip() {
+        let params = Parameters {
+            num_g1_elements_needed: 10,
+            num_g2_elements_needed: 5,
+        };
+
+        let secret = PrivateKey::from_u64(5687);
+        let mut acc = SRS::new(params).unwrap();
+        acc.update(secret);
+
+        let (g1s, g2s) = acc.serialise_with_encoding(PointEncoding::Uncompressed);
+        assert!(g1s.iter().all(|point| point.len() == 2 + 2 * 96));
+        assert!(g2s.iter().all(|point| point.len() == 2 + 2 * 192));
+
+        let deserialised_srs = SRS::deserialise((&g1s, &g2s), params).unwrap();
+        assert_eq!(acc, deserialised_srs);
+
+        let srs_json = SRSJson::with_encoding(&acc, PointEncoding::Uncompressed);
+        assert_eq!(SRS::try_from(&srs_json).unwrap(), acc);
+    }
+
+    #[test]
+    fn witness_json_roundtrip() {
+        let params = Parameters {
+            num_g1_elements_needed: 10,
+            num_g2_elements_needed: 5,
+        };
+        let mut acc = SRS::new(params).unwrap();
+        let mut witness = Witness::default();
+
+        let proof = acc.update(PrivateKey::from_u64(5687));
+        witness.push(&proof, None);
+        let proof = acc.update(PrivateKey::from_u64(1234));
+        let signature = G1Projective::prime_subgroup_generator().mul(Fr::from(42u64).into_repr());
+        witness.push(&proof, Some(signature));
+
+        let srs_json = SRSJson::with_witness(&acc, &witness);
+        let json = serde_json::to_value(&srs_json).unwrap();
+        assert!(json.get("potPubkey").is_none());
+        assert_eq!(
+            json["witness"]["runningProducts"].as_array().unwrap().len(),
+            3
+        );
+        assert_eq!(json["witness"]["blsSignatures"][1], "");
+
+        let srs_json: SRSJson = serde_json::from_value(json).unwrap();
+        assert_eq!(SRS::try_from(&srs_json).unwrap(), acc);
+        assert_eq!(
+            Witness::try_from(srs_json.witness().unwrap()).unwrap(),
+            witness
+        );
+    }
+
+    #[test]
+    fn identity_signature_serialise_roundtrip() {
+        let identity = "eth|0x0000000000000000000000000000000000000000";
+        let signature = PrivateKey::from_u64(123).sign_identity(identity);
+
+        let hex_str = serialise_identity_signature(&signature);
+        assert_eq!(hex_str.len(), 2 + 2 * G1_SERIALISED_SIZE);
+
+        let got = deserialise_identity_signature(&hex_str).unwrap();
+        assert_eq!(got, signature);
+    }
+
+    #[test]
+    fn srs_deserialise_errors() {
+        let params = Parameters {
+            num_g1_elements_needed: 4,
+            num_g2_elements_needed: 2,
+        };
+        let (g1s, g2s) = SRS::new(params).unwrap().serialise();
+
+        let wrong_params = Parameters {
+            num_g1_elements_needed: 5,
+            num_g2_elements_needed: 2,
+        };
+        assert_eq!(
+            SRS::deserialise((&g1s, &g2s), wrong_params),
+            Err(DeserialiseError::WrongNumberOfPowers {
+                group: Group::G1,
+                expected: 5,
+                got: 4
+            })
+        );
+
+        let mut bad_g1s = g1s.clone();
+        bad_g1s[2] = bad_g1s[2].trim_start_matches("0x").to_string();
+        assert_eq!(
+            SRS::deserialise((&bad_g1s, &g2s), params),
+            Err(DeserialiseError::InvalidPoint {
+                group: Group::G1,
+                index: 2,
+                error: PointDeserialiseError::MissingHexPrefix
+            })
+        );
+
+        let mut bad_g2s = g2s.clone();
+        bad_g2s[1] = "0xzz".to_string();
+        assert_eq!(
+            SRS::deserialise((&g1s, &bad_g2s), params),
+            Err(DeserialiseError::InvalidPoint {
+                group: Group::G2,
+                index: 1,
+                error: PointDeserialiseError::InvalidHex
+            })
+        );
+
+        bad_g2s[1] = g1s[1].clone();
+        assert_eq!(
+            SRS::deserialise((&g1s, &bad_g2s), params),
+            Err(DeserialiseError::InvalidPoint {
+                group: Group::G2,
+                index: 1,
+                error: PointDeserialiseError::InvalidLength {
+                    expected: G2_SERIALISED_SIZE,
+                    got: G1_SERIALISED_SIZE
+                }
+            })
+        );
+    }
 }
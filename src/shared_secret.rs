@@ -1,5 +1,22 @@
-use ark_bls12_381::{G1Projective, G2Affine, G2Projective};
-use ark_ec::{AffineCurve, PairingEngine};
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField};
+use itertools::Itertools;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::VerificationError,
+    interop_point_encoding::{serialize_g1, serialize_g2},
+};
+
+// The domain separation tag used when deriving the scalars for the batched check
+const BATCH_SCALARS_DST: &[u8] = b"SMALL-POWERS-OF-TAU-V01-CHAIN-BATCH";
+
+// The number of links whose Miller loops are computed together.
+// This bounds the memory used by the prepared G2 points.
+const MILLER_LOOP_CHUNK_SIZE: usize = 1024;
 
 // A shared secret proof proves that a point was necessarily created by multiplying the discrete log of a series of previous points
 //
@@ -36,24 +53,110 @@ impl SharedSecretChain {
 
     // Verifies a shared secret chain, each srs is checked to have been transformed from the previous one
     // using the specified witness
-    pub fn verify(&self) -> bool {
+    //
+    // All of the links are first checked at once. If that fails, each link is checked on its own
+    // so that we can find the bad link.
+    //
+    // On failure, the index of the first witness that does not attest to its transition is returned
+    pub fn verify(&self) -> Result<(), VerificationError> {
+        if self.verify_batched() {
+            return Ok(());
+        }
+        self.verify_each_link()
+    }
+
+    // Checks each link of the chain with two pairings
+    fn verify_each_link(&self) -> Result<(), VerificationError> {
         // Overlapping window of two; see example: https://gist.github.com/rust-play/d83ae8ffdbf24f17612e05dc75c2ee06
         // Group accumulated points into overlapping pairs
         let acc_pairs = self.accumulated_points.as_slice().windows(2);
 
         let gen_g2 = G2Affine::prime_subgroup_generator();
 
-        for (acc_pair, witness) in acc_pairs.zip(&self.witnesses) {
+        for (index, (acc_pair, witness)) in acc_pairs.zip(&self.witnesses).enumerate() {
             let prev_acc = acc_pair[0];
             let next_acc = acc_pair[1];
-            let p1 = ark_bls12_381::Bls12_381::pairing(next_acc, gen_g2);
-            let p2 = ark_bls12_381::Bls12_381::pairing(prev_acc, *witness);
+            let p1 = Bls12_381::pairing(next_acc, gen_g2);
+            let p2 = Bls12_381::pairing(prev_acc, *witness);
             if p1 != p2 {
-                return false;
+                return Err(VerificationError::BrokenChain { index });
             }
         }
-        true
+        Ok(())
+    }
+
+    // Checks every link of the chain at once, using a random linear combination.
+    //
+    // Link i holds when e(A_{i+1}, G2) == e(A_i, W_i). With random scalars r_i, we check that
+    // e(sum r_i * A_{i+1}, G2) * prod e(-r_i * A_i, W_i) == 1
+    // which needs one Miller loop per link, but only a single final exponentiation.
+    //
+    // The scalars are derived by hashing the chain, so a bad link can only cancel out with
+    // another bad link with negligible probability.
+    fn verify_batched(&self) -> bool {
+        #[cfg(feature = "parallel")]
+        use rayon::prelude::*;
+
+        let num_links = self.witnesses.len();
+        if num_links == 0 {
+            return true;
+        }
+
+        let accumulated_points =
+            G1Projective::batch_normalization_into_affine(&self.accumulated_points);
+        let witnesses = G2Projective::batch_normalization_into_affine(&self.witnesses);
+        let scalars = batching_scalars(&accumulated_points, &witnesses);
+
+        // sum r_i * A_{i+1}
+        let scalars_repr = scalars.iter().map(|r| r.into_repr()).collect_vec();
+        let combined_next =
+            VariableBaseMSM::multi_scalar_mul(&accumulated_points[1..], &scalars_repr);
+
+        // -r_i * A_i
+        let scaled_prev: Vec<G1Projective> = ark_std::cfg_iter!(accumulated_points[..num_links])
+            .zip(&scalars_repr)
+            .map(|(prev_acc, r)| -prev_acc.mul(*r))
+            .collect();
+        let scaled_prev = G1Projective::batch_normalization_into_affine(&scaled_prev);
+
+        let mut product = Bls12_381::miller_loop(&[(
+            combined_next.into_affine().into(),
+            G2Affine::prime_subgroup_generator().into(),
+        )]);
+        for (prev_chunk, witness_chunk) in scaled_prev
+            .chunks(MILLER_LOOP_CHUNK_SIZE)
+            .zip(witnesses.chunks(MILLER_LOOP_CHUNK_SIZE))
+        {
+            let pairs = prev_chunk
+                .iter()
+                .zip(witness_chunk)
+                .map(|(prev_acc, witness)| ((*prev_acc).into(), (*witness).into()))
+                .collect_vec();
+            product *= Bls12_381::miller_loop(&pairs);
+        }
+
+        match Bls12_381::final_exponentiation(&product) {
+            Some(result) => result.is_one(),
+            None => false,
+        }
+    }
+}
+
+// Derives one 128-bit scalar per link, by seeding a CSPRNG with the hash of the chain
+fn batching_scalars(accumulated_points: &[G1Affine], witnesses: &[G2Affine]) -> Vec<Fr> {
+    let mut hasher = Sha256::new();
+    hasher.update(BATCH_SCALARS_DST);
+    for point in accumulated_points {
+        hasher.update(serialize_g1(point));
     }
+    for witness in witnesses {
+        hasher.update(serialize_g2(witness));
+    }
+
+    let mut rng = ChaCha20Rng::from_seed(hasher.finalize().into());
+    (0..witnesses.len())
+        .map(|_| Fr::from(rng.gen::<u128>()))
+        .collect()
 }
 
 #[cfg(test)]
@@ -63,6 +166,7 @@ mod tests {
     use ark_ff::PrimeField;
 
     use super::SharedSecretChain;
+    use crate::error::VerificationError;
 
     #[test]
     fn shared_secret_smoke() {
@@ -85,31 +189,82 @@ mod tests {
         //
         let a_g1 = g1_generator.mul(a.into_repr());
         chain.extend(a_g1, a_witness);
-        assert!(chain.verify());
+        assert!(chain.verify().is_ok());
         //
         // Add `b` into the product so we have `a * b * G1` as the accumulated point
         let ab_g1 = a_g1.mul(b.into_repr());
         chain.extend(ab_g1, b_witness);
-        assert!(chain.verify());
+        assert!(chain.verify().is_ok());
         //
         // Add `c` into the product so we have `a * b * c * G1` as the accumulated point
         let abc_g1 = ab_g1.mul(c.into_repr());
         chain.extend(abc_g1, c_witness);
-        assert!(chain.verify());
+        assert!(chain.verify().is_ok());
         //
         // Add `d` into the product so we have `a * b * c * d * G1` as the accumulated point
         let abcd_g1 = abc_g1.mul(d.into_repr());
 
         // -- Now for the witness we add `c` instead of `d`. This should verify as false
         chain.extend(abcd_g1, c_witness);
-        assert!(chain.verify() == false);
+        assert_eq!(
+            chain.verify(),
+            Err(VerificationError::BrokenChain { index: 3 })
+        );
         chain.remove_last();
         // -- Now add the correct witness, but the wrong accumulated point
         chain.extend(abc_g1, d_witness);
-        assert!(chain.verify() == false);
+        assert_eq!(
+            chain.verify(),
+            Err(VerificationError::BrokenChain { index: 3 })
+        );
         chain.remove_last();
         // -- Add the correct accumulated point and witness
         chain.extend(abcd_g1, d_witness);
-        assert!(chain.verify())
+        assert!(chain.verify().is_ok())
+    }
+
+    #[test]
+    fn batched_check_does_not_let_errors_cancel() {
+        let g1_generator = G1Projective::prime_subgroup_generator();
+        let g2_generator = G2Projective::prime_subgroup_generator();
+
+        let a = Fr::from(20u64);
+        let b = Fr::from(21u64);
+        let two = Fr::from(2u64);
+
+        // The first link is off by a factor of two and the second link is off by a half,
+        // so the product of the unweighted pairing equations would still hold
+        let mut chain = SharedSecretChain::starting_from(g1_generator);
+        let two_a_g1 = g1_generator.mul((two * a).into_repr());
+        chain.extend(two_a_g1, g2_generator.mul(a.into_repr()));
+        chain.extend(
+            g1_generator.mul((a * b).into_repr()),
+            g2_generator.mul(b.into_repr()),
+        );
+
+        assert!(!chain.verify_batched());
+        assert_eq!(
+            chain.verify(),
+            Err(VerificationError::BrokenChain { index: 0 })
+        );
+    }
+
+    #[test]
+    fn batched_check_matches_each_link() {
+        let g2_generator = G2Projective::prime_subgroup_generator();
+
+        let mut acc = G1Projective::prime_subgroup_generator();
+        let mut chain = SharedSecretChain::starting_from(acc);
+        for i in 0..10u64 {
+            let secret = Fr::from(100 + i);
+            acc = acc.mul(secret.into_repr());
+            chain.extend(acc, g2_generator.mul(secret.into_repr()));
+        }
+        assert!(chain.verify_batched());
+        assert!(chain.verify_each_link().is_ok());
+
+        // The empty chain is valid
+        let chain = SharedSecretChain::starting_from(acc);
+        assert!(chain.verify_batched());
     }
 }
@@ -1,8 +1,18 @@
-use crate::{keypair::PrivateKey, update_proof::UpdateProof};
-use ark_bls12_381::{Fr, G1Projective, G2Projective};
-use ark_ec::{msm::VariableBaseMSM, PairingEngine, ProjectiveCurve};
-use ark_ff::{PrimeField, Zero};
+use crate::{
+    error::{Group, VerificationError},
+    fiat_shamir::derive_random_element,
+    keypair::PrivateKey,
+    secret_memory::SecretVec,
+    update_proof::UpdateProof,
+};
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{
+    msm::VariableBaseMSM, wnaf::WnafContext, AffineCurve, PairingEngine, ProjectiveCurve,
+};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 // Structured Reference String. Stores the powers of tau
 // in G1 and G2
@@ -14,9 +24,11 @@ pub struct SRS {
     tau_g2: Vec<G2Projective>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Parameters {
+    #[serde(rename = "numG1Powers")]
     pub(crate) num_g1_elements_needed: usize,
+    #[serde(rename = "numG2Powers")]
     pub(crate) num_g2_elements_needed: usize,
 }
 
@@ -40,7 +52,7 @@ impl SRS {
     pub fn from_vectors(g1s: Vec<G1Projective>, g2s: Vec<G2Projective>) -> Option<SRS> {
         let cond = g1s.len() > 1 && g2s.len() > 1;
         if !cond {
-            return None;
+            None
         } else {
             Some(SRS {
                 tau_g1: g1s,
@@ -79,43 +91,26 @@ impl SRS {
 
     // Updates the srs and produces a proof of this update
     pub fn update(&mut self, private_key: PrivateKey) -> UpdateProof {
-        self.update_srs(private_key.tau);
+        self.update_srs(&private_key.tau);
         let updated_tau = self.tau_g1[1];
 
         UpdateProof {
-            commitment_to_secret: private_key.to_public(),
+            commitment_to_secret: private_key.to_public().point(),
             new_accumulated_point: updated_tau,
         }
     }
 
     // Updates the group elements using a users private key
-    fn update_srs(&mut self, private_key: Fr) {
-        use ark_ec::wnaf::WnafContext;
-
-        #[cfg(feature = "parallel")]
-        use rayon::prelude::*;
-
-        let max_number_elements = std::cmp::max(self.tau_g1.len(), self.tau_g2.len());
-
-        let powers_of_priv_key = vandemonde_challenge(private_key, max_number_elements - 1);
-
-        let wnaf = WnafContext::new(3);
-
-        ark_std::cfg_iter_mut!(self.tau_g1)
-            // Skip the degree-0 element as it does not get updated
-            .skip(1)
-            .zip(&powers_of_priv_key)
-            .for_each(|(tg1, priv_pow)| {
-                *tg1 = wnaf.mul(*tg1, priv_pow);
-            });
-
-        ark_std::cfg_iter_mut!(self.tau_g2)
-            // Skip the degree-0 element as it does not get updated
-            .skip(1)
-            .zip(&powers_of_priv_key)
-            .for_each(|(tg2, priv_pow)| {
-                *tg2 = wnaf.mul(*tg2, priv_pow);
-            })
+    //
+    // The powers of the private key are wiped once the group elements are updated
+    fn update_srs(&mut self, private_key: &Fr) {
+        // Skip the degree-0 elements as they do not get updated
+        let mut next_power = *private_key;
+        multiply_by_powers(&mut self.tau_g1[1..], &mut next_power, private_key);
+
+        next_power = *private_key;
+        multiply_by_powers(&mut self.tau_g2[1..], &mut next_power, private_key);
+        next_power.zeroize();
     }
 
     // Verify whether the transition from one SRS to the other was valid
@@ -128,24 +123,22 @@ impl SRS {
         after: &SRS,
         update_proofs: &[UpdateProof],
         random_element: Fr,
-    ) -> bool {
+    ) -> Result<(), VerificationError> {
         // If there are no update proofs and the user calls this method
-        // we return False regardless. Even if `before===after`
+        // we return an error regardless. Even if `before===after`
         // We do not accept a transition without a proof
         let last_update = match update_proofs.last() {
             Some(update) => update,
-            None => return false,
+            None => return Err(VerificationError::NoUpdateProofs),
         };
 
         // 1. Check that the updates finished at the ending SRS
         if after.tau_g1[1] != last_update.new_accumulated_point {
-            return false;
+            return Err(VerificationError::FinalPointMismatch);
         }
 
         // 2. Check the update proofs are correct and form a chain of updates
-        if !UpdateProof::verify_chain(before.tau_g1[1], update_proofs) {
-            return false;
-        }
+        UpdateProof::verify_chain(before.tau_g1[1], update_proofs)?;
 
         // 3. Check that the degree-1 component is not the identity element
         // No need to check the other elements because the structure check will fail
@@ -155,41 +148,43 @@ impl SRS {
         // used was also not zero. Which implies that the public key inside of the
         // update proof is not the identity element or the update proof check will fail.
         if after.tau_g1[1].is_zero() {
-            return false;
+            return Err(VerificationError::IdentityElement(Group::G1));
         }
         if after.tau_g2[1].is_zero() {
-            return false;
+            return Err(VerificationError::IdentityElement(Group::G2));
         }
 
         // 3. Check that the new SRS goes up in incremental powers
-        if !after.structure_check_opt(random_element) {
-            return false;
-        }
-
-        true
+        after.structure_check_opt(random_element)
     }
 
     // Check that the list of G1 and G2 elements are in the
     // prime order subgroup
     // These points are already checked to be on the curve which is _cheap_
     // so that we do not become victim to the invalid curve attack
-    pub fn subgroup_check(&self) -> bool {
+    pub fn subgroup_check(&self) -> Result<(), VerificationError> {
         use crate::interop_subgroup_checks::{g1, g2};
 
         let g1_points_affine = G1Projective::batch_normalization_into_affine(&self.tau_g1);
         let g2_points_affine = G2Projective::batch_normalization_into_affine(&self.tau_g2);
-        for point in g1_points_affine {
-            if !g1::is_in_correct_subgroup_assuming_on_curve(&point) {
-                return false;
+        for (index, point) in g1_points_affine.iter().enumerate() {
+            if !g1::is_in_correct_subgroup_assuming_on_curve(point) {
+                return Err(VerificationError::SubgroupCheckFailed {
+                    group: Group::G1,
+                    index,
+                });
             }
         }
-        for point in g2_points_affine {
-            if !g2::is_in_correct_subgroup_assuming_on_curve(&point) {
-                return false;
+        for (index, point) in g2_points_affine.iter().enumerate() {
+            if !g2::is_in_correct_subgroup_assuming_on_curve(point) {
+                return Err(VerificationError::SubgroupCheckFailed {
+                    group: Group::G2,
+                    index,
+                });
             }
         }
 
-        true
+        Ok(())
     }
 
     // Verify that a single update was applied to transition `before` to `after`
@@ -200,94 +195,101 @@ impl SRS {
         after: &SRS,
         update_proof: &UpdateProof,
         random_element: Fr,
-    ) -> bool {
+    ) -> Result<(), VerificationError> {
         SRS::verify_updates(before, after, &[*update_proof], random_element)
     }
 
+    // Like `verify_updates`, but the random element is derived by hashing the SRSs and the
+    // update proofs, instead of being chosen by the caller
+    pub fn verify_updates_fiat_shamir(
+        before: &SRS,
+        after: &SRS,
+        update_proofs: &[UpdateProof],
+    ) -> Result<(), VerificationError> {
+        let random_element = derive_random_element(before, after, update_proofs);
+        SRS::verify_updates(before, after, update_proofs, random_element)
+    }
+
+    // Like `verify_update`, but the random element is derived by hashing the SRSs and the
+    // update proof, instead of being chosen by the caller
+    pub fn verify_update_fiat_shamir(
+        before: &SRS,
+        after: &SRS,
+        update_proof: &UpdateProof,
+    ) -> Result<(), VerificationError> {
+        SRS::verify_updates_fiat_shamir(before, after, &[*update_proof])
+    }
+
     // We detail the algorithm here: https://hackmd.io/C0lk1xyWQryGggRlNYDqZw#Appendix-1---Incremental-powers-of-tau-check-Batching
     // This allows us to check that the SRS has the correct structure using only 1 pairing
-    pub fn structure_check_opt(&self, random_element: Fr) -> bool {
+    //
+    // The G1 check e(L_1, tau_g2_1) == e(R_1, tau_g2_0) and the G2 check
+    // e(tau_g1_1, L_2) == e(tau_g1_0, R_2) are folded together with the scalar s = r^n,
+    // where n is the number of powers of r used in the commitments, so that both are
+    // verified by a single product of pairings:
+    //
+    // e(L_1, tau_g2_1) * e(-R_1, tau_g2_0) * e(s * tau_g1_1, L_2) * e(-s * tau_g1_0, R_2) == 1
+    //
+    // Only when this fails do we check the G1 equation on its own, to report which group failed.
+    pub fn structure_check_opt(&self, random_element: Fr) -> Result<(), VerificationError> {
         // Check will always pass if the random element is zero
-        // We return false in this case
+        // We return an error in this case
         if random_element.is_zero() {
-            return false;
+            return Err(VerificationError::ZeroRandomElement);
         }
 
         let len_g1 = self.tau_g1.len();
         let len_g2 = self.tau_g2.len();
 
         let max_number_elements = std::cmp::max(len_g1, len_g2);
-        let rand_pow = vandemonde_challenge(random_element, max_number_elements - 1);
-
-        let tau_g2_0 = self.tau_g2[0];
-        let tau_g2_1 = self.tau_g2[1];
-
-        let tau_g1_0 = self.tau_g1[0];
-        let tau_g1_1 = self.tau_g1[1];
+        let rand_pow = vandemonde_challenge(random_element, max_number_elements);
+        // The last power is not used in the commitments, so it is used to fold the checks together
+        let fold_scalar = rand_pow[max_number_elements - 1];
 
-        let scalars = rand_pow
-            .into_iter()
+        let scalars = rand_pow[..max_number_elements - 1]
+            .iter()
             .map(|scalar| scalar.into_repr())
             .collect_vec();
 
-        // All elements in G1 except the last element
-        #[allow(non_snake_case)]
-        let L = &self.tau_g1[0..len_g1 - 1];
-        assert_eq!(L.len(), len_g1 - 1);
+        let tau_g1 = G1Projective::batch_normalization_into_affine(&self.tau_g1);
+        let tau_g2 = G2Projective::batch_normalization_into_affine(&self.tau_g2);
 
-        // All elements in G1 except the first element
+        // Commit to all elements except the last element (L) and all elements
+        // except the first element (R)
         #[allow(non_snake_case)]
-        let R = &self.tau_g1[1..];
-        assert_eq!(R.len(), len_g1 - 1);
-
-        #[allow(non_snake_case)]
-        let L_comm = VariableBaseMSM::multi_scalar_mul(
-            &L.iter().map(|element| element.into_affine()).collect_vec(),
-            &scalars,
-        );
-        #[allow(non_snake_case)]
-        let R_comm = VariableBaseMSM::multi_scalar_mul(
-            &R.iter().map(|element| element.into_affine()).collect_vec(),
-            &scalars,
-        );
-        let p1 = ark_bls12_381::Bls12_381::pairing(L_comm, tau_g2_1);
-        let p2 = ark_bls12_381::Bls12_381::pairing(R_comm, tau_g2_0);
-
-        if p1 != p2 {
-            return false;
-        }
-
-        // Check G2
-
-        // All elements in G2 except the last element
-        #[allow(non_snake_case)]
-        let L = &self.tau_g2[0..len_g2 - 1];
-        assert_eq!(L.len(), len_g2 - 1);
-
-        // All elements in G2 except the first element
+        let L1_comm = VariableBaseMSM::multi_scalar_mul(&tau_g1[0..len_g1 - 1], &scalars);
         #[allow(non_snake_case)]
-        let R = &self.tau_g2[1..];
-        assert_eq!(R.len(), len_g2 - 1);
-
+        let R1_comm = VariableBaseMSM::multi_scalar_mul(&tau_g1[1..], &scalars);
         #[allow(non_snake_case)]
-        let L_comm = VariableBaseMSM::multi_scalar_mul(
-            &L.iter().map(|element| element.into_affine()).collect_vec(),
-            &scalars,
-        );
+        let L2_comm = VariableBaseMSM::multi_scalar_mul(&tau_g2[0..len_g2 - 1], &scalars);
         #[allow(non_snake_case)]
-        let R_comm = VariableBaseMSM::multi_scalar_mul(
-            &R.iter().map(|element| element.into_affine()).collect_vec(),
-            &scalars,
-        );
-
-        let p1 = ark_bls12_381::Bls12_381::pairing(tau_g1_1, L_comm);
-        let p2 = ark_bls12_381::Bls12_381::pairing(tau_g1_0, R_comm);
+        let R2_comm = VariableBaseMSM::multi_scalar_mul(&tau_g2[1..], &scalars);
+
+        let fold_scalar = fold_scalar.into_repr();
+        let g1_points = G1Projective::batch_normalization_into_affine(&[
+            L1_comm,
+            -R1_comm,
+            tau_g1[1].mul(fold_scalar),
+            -tau_g1[0].mul(fold_scalar),
+        ]);
+        let g2_points = G2Projective::batch_normalization_into_affine(&[L2_comm, R2_comm]);
+
+        let g1_check = [(g1_points[0], tau_g2[1]), (g1_points[1], tau_g2[0])];
+        let g2_check = [(g1_points[2], g2_points[0]), (g1_points[3], g2_points[1])];
+
+        if pairing_product_is_one(g1_check.iter().chain(&g2_check)) {
+            return Ok(());
+        }
 
-        p1 == p2
+        if !pairing_product_is_one(&g1_check) {
+            return Err(VerificationError::G1StructureCheckFailed);
+        }
+        Err(VerificationError::G2StructureCheckFailed)
     }
 
     // Inefficiently checks that the srs has the correct structure
     // Meaning each subsequent element is increasing the index of tau for both G_1 and G_2 elements
+    #[cfg(test)]
     fn structure_check(&self) -> bool {
         let tau_g2_0 = self.tau_g2[0];
         let tau_g2_1 = self.tau_g2[1];
@@ -332,7 +334,80 @@ fn vandemonde_challenge(x: Fr, n: usize) -> Vec<Fr> {
     challenges
 }
 
+// Multiplies each point by successive powers of the secret x, starting at `next_power`
+//
+// Afterwards `next_power` holds the power for the point after the last one, so that a long list
+// of points can be updated in chunks. The powers are held in a buffer that is wiped on drop.
+pub(crate) fn multiply_by_powers<G: ProjectiveCurve<ScalarField = Fr>>(
+    points: &mut [G],
+    next_power: &mut Fr,
+    x: &Fr,
+) {
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    let mut powers = SecretVec::with_capacity(points.len());
+    for _ in 0..points.len() {
+        powers.push(*next_power);
+        *next_power *= x;
+    }
+
+    ark_std::cfg_iter_mut!(points)
+        .zip(&*powers)
+        .for_each(|(point, power)| {
+            *point = secret_wnaf_mul(*point, power);
+        });
+}
+
+// The window size used when multiplying a point by a power of the secret
+const WNAF_WINDOW_SIZE: usize = 3;
+
+// Multiplies a point by a secret scalar using wNAF, wiping the scalar's limbs and its wNAF digits
+//
+// This follows `WnafContext::mul`, which leaves both behind in memory
+fn secret_wnaf_mul<G: ProjectiveCurve<ScalarField = Fr>>(base: G, scalar: &Fr) -> G {
+    let table = WnafContext::new(WNAF_WINDOW_SIZE).table(base);
+
+    let mut scalar_repr = scalar.into_repr();
+    let mut scalar_wnaf = scalar_repr
+        .find_wnaf(WNAF_WINDOW_SIZE)
+        .expect("the window size is valid");
+    scalar_repr.zeroize();
+
+    let mut result = G::zero();
+    let mut found_non_zero = false;
+    for digit in scalar_wnaf.iter().rev() {
+        if found_non_zero {
+            result.double_in_place();
+        }
+        if *digit != 0 {
+            found_non_zero = true;
+            if *digit > 0 {
+                result += &table[(digit / 2) as usize];
+            } else {
+                result -= &table[((-digit) / 2) as usize];
+            }
+        }
+    }
+    scalar_wnaf.zeroize();
+
+    result
+}
+
+// Checks that the product of the pairings of each pair of points is the identity,
+// using a single final exponentiation
+pub(crate) fn pairing_product_is_one<'a>(
+    pairs: impl IntoIterator<Item = &'a (G1Affine, G2Affine)>,
+) -> bool {
+    let prepared = pairs
+        .into_iter()
+        .map(|(g1, g2)| ((*g1).into(), (*g2).into()))
+        .collect_vec();
+    Bls12_381::product_of_pairings(&prepared).is_one()
+}
+
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
     use ark_ff::{Field, One, PrimeField};
@@ -346,12 +421,70 @@ mod tests {
         let secret = PrivateKey::from_u64(0);
         let update_proof = after.update(secret);
 
-        assert!(!SRS::verify_update(
+        assert_eq!(
+            SRS::verify_update(&before, &after, &update_proof, Fr::from(123456789)),
+            Err(VerificationError::IdentityElement(Group::G1))
+        );
+    }
+    #[test]
+    fn verify_updates_reports_failure() {
+        let before = SRS::new_for_kzg(100);
+        let mut after = before.clone();
+
+        let update_proof_1 = after.update(PrivateKey::from_u64(252));
+        let update_proof_2 = after.update(PrivateKey::from_u64(512));
+        let random_element = Fr::from(123456789);
+
+        assert_eq!(
+            SRS::verify_updates(&before, &after, &[], random_element),
+            Err(VerificationError::NoUpdateProofs)
+        );
+        assert_eq!(
+            SRS::verify_updates(
+                &before,
+                &after,
+                &[update_proof_2, update_proof_1],
+                random_element
+            ),
+            Err(VerificationError::FinalPointMismatch)
+        );
+        assert_eq!(
+            SRS::verify_updates(&before, &after, &[update_proof_2], random_element),
+            Err(VerificationError::BrokenChain { index: 0 })
+        );
+        assert_eq!(
+            SRS::verify_updates(
+                &before,
+                &after,
+                &[update_proof_1, update_proof_2],
+                Fr::zero()
+            ),
+            Err(VerificationError::ZeroRandomElement)
+        );
+        assert!(SRS::verify_updates(
             &before,
             &after,
-            &update_proof,
-            Fr::from(123456789)
-        ));
+            &[update_proof_1, update_proof_2],
+            random_element
+        )
+        .is_ok());
+    }
+    #[test]
+    fn verify_update_fiat_shamir_smoke() {
+        let before = SRS::new_for_kzg(100);
+        let mut after = before.clone();
+        let update_proof = after.update(PrivateKey::from_u64(252));
+
+        assert!(SRS::verify_update_fiat_shamir(&before, &after, &update_proof).is_ok());
+
+        // The contributor cannot know the random element before they send the SRS,
+        // so changing a power of tau is caught by the structure check
+        let mut bad_after = after.clone();
+        bad_after.tau_g1[3] = bad_after.tau_g1[3].double();
+        assert_eq!(
+            SRS::verify_update_fiat_shamir(&before, &bad_after, &update_proof),
+            Err(VerificationError::G1StructureCheckFailed)
+        );
     }
     #[test]
     fn zero_pow_zero() {
@@ -362,7 +495,7 @@ mod tests {
         // Note that in the wnaf update method, we do not modify the degree-0 element
         // which has the same effect when 0^0 = 1
         let secret = PrivateKey::from_u64(0);
-        let value = secret.tau.pow(&[0]);
+        let value = secret.tau.pow([0]);
 
         assert!(value.is_one())
     }
@@ -376,16 +509,16 @@ mod tests {
         let mut expected_srs = got_srs.clone();
 
         let secret = PrivateKey::from_u64(123456789);
-        let secret_fr = secret.tau.clone();
+        let secret_fr = secret.tau;
 
         got_srs.update(secret);
 
         for (index, tg1) in expected_srs.tau_g1.iter_mut().enumerate() {
-            let secret_pow_i = secret_fr.pow(&[index as u64]);
+            let secret_pow_i = secret_fr.pow([index as u64]);
             *tg1 = tg1.mul(secret_pow_i.into_repr())
         }
         for (index, tg2) in expected_srs.tau_g2.iter_mut().enumerate() {
-            let secret_pow_i = secret_fr.pow(&[index as u64]);
+            let secret_pow_i = secret_fr.pow([index as u64]);
             *tg2 = tg2.mul(secret_pow_i.into_repr())
         }
 
@@ -412,7 +545,8 @@ mod tests {
         assert!(UpdateProof::verify_chain(
             before_update_1_degree_1,
             &[update_proof_1, update_proof_2, update_proof_3,]
-        ));
+        )
+        .is_ok());
     }
 
     #[test]
@@ -421,6 +555,37 @@ mod tests {
 
         let mut acc = SRS::new_for_kzg(100);
         acc.update(secret_a);
-        assert!(acc.structure_check_opt(Fr::from(100u64)));
+        assert!(acc.structure_check());
+        assert!(acc.structure_check_opt(Fr::from(100u64)).is_ok());
+    }
+
+    #[test]
+    fn structure_check_opt_reports_group() {
+        let mut srs = SRS::new(Parameters::new(100, 5)).unwrap();
+        srs.update(PrivateKey::from_u64(252));
+        let random_element = Fr::from(123456789u64);
+
+        let mut bad_srs = srs.clone();
+        bad_srs.tau_g1[5] = bad_srs.tau_g1[6];
+        assert!(!bad_srs.structure_check());
+        assert_eq!(
+            bad_srs.structure_check_opt(random_element),
+            Err(VerificationError::G1StructureCheckFailed)
+        );
+
+        let mut bad_srs = srs.clone();
+        bad_srs.tau_g2[3] = bad_srs.tau_g2[4];
+        assert!(!bad_srs.structure_check());
+        assert_eq!(
+            bad_srs.structure_check_opt(random_element),
+            Err(VerificationError::G2StructureCheckFailed)
+        );
+
+        // When both groups are wrong, the G1 error is reported
+        bad_srs.tau_g1[5] = bad_srs.tau_g1[6];
+        assert_eq!(
+            bad_srs.structure_check_opt(random_element),
+            Err(VerificationError::G1StructureCheckFailed)
+        );
     }
 }
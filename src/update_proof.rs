@@ -2,8 +2,14 @@
 // - One knows the discrete log to a secret `p` via KoE
 // - `p` was used to update an existing point A to a new point A'
 
-use crate::shared_secret::SharedSecretChain;
+use crate::{
+    error::{Group, VerificationError},
+    interop_subgroup_checks::g2,
+    keypair::{verify_bls_g1_signature, PublicKey, IDENTITY_SIGNATURE_DST},
+    shared_secret::SharedSecretChain,
+};
 use ark_bls12_381::{G1Projective, G2Projective};
+use ark_ec::ProjectiveCurve;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct UpdateProof {
@@ -15,11 +21,32 @@ pub struct UpdateProof {
 }
 
 impl UpdateProof {
+    // The public key of the contributor that created this update proof
+    //
+    // Returns None if the commitment is not in the prime order subgroup
+    pub fn public_key(&self) -> Option<PublicKey> {
+        PublicKey::new(self.commitment_to_secret)
+    }
+
     // Verifies a list of update of update proofs using `SharedSecretChain` as a subroutine
+    //
+    // Each commitment is subgroup checked first, since it is the public key of the contributor.
+    // The whole chain is then checked with a single multi-pairing, and only when that fails
+    // is each proof checked on its own to find the first bad proof
     pub(crate) fn verify_chain(
         starting_point: G1Projective,
         update_proofs: &[UpdateProof],
-    ) -> bool {
+    ) -> Result<(), VerificationError> {
+        for (index, update_proof) in update_proofs.iter().enumerate() {
+            let commitment = update_proof.commitment_to_secret.into_affine();
+            if !g2::is_in_correct_subgroup_assuming_on_curve(&commitment) {
+                return Err(VerificationError::SubgroupCheckFailed {
+                    group: Group::G2,
+                    index,
+                });
+            }
+        }
+
         let mut chain = SharedSecretChain::starting_from(starting_point);
 
         for update_proof in update_proofs {
@@ -33,4 +60,147 @@ impl UpdateProof {
 
         chain.verify()
     }
+
+    // Verifies that `signature` is a BLS signature over `identity`, made with the secret
+    // that was committed to in this update proof.
+    //
+    // This checks that e(signature, G2) == e(H(identity), commitment_to_secret)
+    pub fn verify_identity_signature(&self, identity: &str, signature: G1Projective) -> bool {
+        verify_bls_g1_signature(
+            identity.as_bytes(),
+            IDENTITY_SIGNATURE_DST,
+            self.commitment_to_secret,
+            signature,
+        )
+    }
+
+    // Verifies the identity signature for each update proof in a chain.
+    //
+    // The identities and signatures are in the same order as the update proofs.
+    pub fn verify_identity_signatures<S: AsRef<str>>(
+        update_proofs: &[UpdateProof],
+        identities: &[S],
+        signatures: &[G1Projective],
+    ) -> Result<(), VerificationError> {
+        if update_proofs.len() != identities.len() || update_proofs.len() != signatures.len() {
+            return Err(VerificationError::SignatureCountMismatch {
+                update_proofs: update_proofs.len(),
+                identities: identities.len(),
+                signatures: signatures.len(),
+            });
+        }
+
+        for (index, ((update_proof, identity), signature)) in update_proofs
+            .iter()
+            .zip(identities)
+            .zip(signatures)
+            .enumerate()
+        {
+            if !update_proof.verify_identity_signature(identity.as_ref(), *signature) {
+                return Err(VerificationError::InvalidIdentitySignature { index });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        keypair::PrivateKey,
+        srs::{Parameters, SRS},
+    };
+    use ark_ff::Zero;
+
+    #[test]
+    fn identity_signatures_smoke() {
+        let mut srs = SRS::new(Parameters::new(4, 2)).unwrap();
+        let identities = [
+            "eth|0x0000000000000000000000000000000000000001",
+            "git|1234|alice",
+        ];
+
+        let mut update_proofs = Vec::new();
+        let mut signatures = Vec::new();
+        for (i, identity) in identities.iter().enumerate() {
+            let secret = PrivateKey::from_u64(100 + i as u64);
+            signatures.push(secret.sign_identity(identity));
+            update_proofs.push(srs.update(secret));
+        }
+
+        assert!(
+            UpdateProof::verify_identity_signatures(&update_proofs, &identities, &signatures)
+                .is_ok()
+        );
+
+        // The signatures are bound to the identities
+        let swapped_identities = [identities[1], identities[0]];
+        assert_eq!(
+            UpdateProof::verify_identity_signatures(
+                &update_proofs,
+                &swapped_identities,
+                &signatures
+            ),
+            Err(VerificationError::InvalidIdentitySignature { index: 0 })
+        );
+
+        // A signature made with a different secret does not verify
+        let mut bad_signatures = signatures.clone();
+        bad_signatures[1] = PrivateKey::from_u64(5).sign_identity(identities[1]);
+        assert_eq!(
+            UpdateProof::verify_identity_signatures(&update_proofs, &identities, &bad_signatures),
+            Err(VerificationError::InvalidIdentitySignature { index: 1 })
+        );
+
+        // The identity element is never a valid signature
+        assert!(!update_proofs[0].verify_identity_signature(identities[0], G1Projective::zero()));
+
+        assert_eq!(
+            UpdateProof::verify_identity_signatures(&update_proofs, &identities, &signatures[..1]),
+            Err(VerificationError::SignatureCountMismatch {
+                update_proofs: 2,
+                identities: 2,
+                signatures: 1
+            })
+        );
+    }
+
+    #[test]
+    fn verify_chain_subgroup_checks_commitments() {
+        use ark_bls12_381::{Fq2, G2Affine};
+        use ark_ec::AffineCurve;
+        use ark_ff::One;
+
+        let mut srs = SRS::new(Parameters::new(4, 2)).unwrap();
+        let starting_point = srs.g1_elements()[1];
+        let mut update_proofs = vec![
+            srs.update(PrivateKey::from_u64(100)),
+            srs.update(PrivateKey::from_u64(200)),
+        ];
+        assert!(UpdateProof::verify_chain(starting_point, &update_proofs).is_ok());
+        assert_eq!(
+            update_proofs[1].public_key(),
+            Some(PrivateKey::from_u64(200).to_public())
+        );
+
+        // A point on the curve that is not in the prime order subgroup
+        let mut x = Fq2::one();
+        let point = loop {
+            if let Some(point) = G2Affine::get_point_from_x(x, true) {
+                break point;
+            }
+            x += Fq2::one();
+        };
+        update_proofs[1].commitment_to_secret = point.into_projective();
+        assert_eq!(update_proofs[1].public_key(), None);
+        assert_eq!(
+            UpdateProof::verify_chain(starting_point, &update_proofs),
+            Err(VerificationError::SubgroupCheckFailed {
+                group: Group::G2,
+                index: 1
+            })
+        );
+    }
 }
@@ -0,0 +1,165 @@
+// The witness is the history of a ceremony, as recorded in the transcript.
+//
+// For each contribution it holds:
+// - The degree-1 G1 element of the SRS after the contribution was applied (running product)
+// - The public key of the contributor (pot pubkey)
+// - An optional BLS signature over the contributors identity
+//
+// The first entry is for the starting SRS, and so the running product and pot pubkey are
+// the generators and there is no signature.
+use ark_bls12_381::{Fr, G1Projective, G2Projective};
+use ark_ec::ProjectiveCurve;
+use ark_ff::Zero;
+
+use crate::{
+    error::{Group, VerificationError},
+    shared_secret::SharedSecretChain,
+    srs::SRS,
+    update_proof::UpdateProof,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Witness {
+    pub running_products: Vec<G1Projective>,
+    pub pot_pubkeys: Vec<G2Projective>,
+    // Contributors are not required to sign their identity
+    pub bls_signatures: Vec<Option<G1Projective>>,
+}
+
+impl Default for Witness {
+    fn default() -> Self {
+        Witness {
+            running_products: vec![G1Projective::prime_subgroup_generator()],
+            pot_pubkeys: vec![G2Projective::prime_subgroup_generator()],
+            bls_signatures: vec![None],
+        }
+    }
+}
+
+impl Witness {
+    // Records a contribution that was applied to the SRS
+    pub fn push(&mut self, update_proof: &UpdateProof, bls_signature: Option<G1Projective>) {
+        self.running_products
+            .push(update_proof.new_accumulated_point);
+        self.pot_pubkeys.push(update_proof.commitment_to_secret);
+        self.bls_signatures.push(bls_signature);
+    }
+
+    // The number of entries in the witness, including the starting entry
+    pub fn len(&self) -> usize {
+        self.running_products.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.running_products.is_empty()
+    }
+
+    // Verify that the witness is the history of the SRS
+    //
+    // This checks that:
+    // - The witness starts at the generators
+    // - Each running product was created from the previous one, using the secret in the pot pubkey
+    // - The last running product is the degree-1 G1 element of the SRS
+    // - The SRS is in the prime order subgroup and has the correct structure
+    //
+    // A `BrokenChain` error holds the index of the contribution, so the first contribution
+    // which is the second entry in the witness, has index 0.
+    pub fn verify(&self, srs: &SRS, random_element: Fr) -> Result<(), VerificationError> {
+        if self.is_empty()
+            || self.pot_pubkeys.len() != self.len()
+            || self.bls_signatures.len() != self.len()
+        {
+            return Err(VerificationError::InconsistentWitness);
+        }
+
+        // 1. Check that the chain starts at the generators
+        if self.running_products[0] != G1Projective::prime_subgroup_generator()
+            || self.pot_pubkeys[0] != G2Projective::prime_subgroup_generator()
+        {
+            return Err(VerificationError::InvalidStartingPoint);
+        }
+
+        // 2. Check that the chain ends at the SRS
+        let tau_g1_1 = srs.g1_elements()[1];
+        if self.running_products[self.len() - 1] != tau_g1_1 {
+            return Err(VerificationError::FinalPointMismatch);
+        }
+
+        // 3. Check that each running product follows on from the previous
+        let mut chain = SharedSecretChain::starting_from(self.running_products[0]);
+        for (running_product, pot_pubkey) in self.running_products[1..]
+            .iter()
+            .zip(&self.pot_pubkeys[1..])
+        {
+            chain.extend(*running_product, *pot_pubkey);
+        }
+        chain.verify()?;
+
+        // 4. Check the SRS itself
+        //
+        // If there were no contributions, then the SRS is the starting SRS and
+        // the degree-1 element will be the generator.
+        if tau_g1_1.is_zero() {
+            return Err(VerificationError::IdentityElement(Group::G1));
+        }
+        if srs.g2_elements()[1].is_zero() {
+            return Err(VerificationError::IdentityElement(Group::G2));
+        }
+        srs.subgroup_check()?;
+        srs.structure_check_opt(random_element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::PrivateKey, srs::Parameters};
+
+    #[test]
+    fn witness_verify_smoke() {
+        let mut srs = SRS::new(Parameters::new(10, 3)).unwrap();
+        let mut witness = Witness::default();
+        let random_element = Fr::from(123456789u64);
+
+        // The starting SRS is valid with the default witness
+        assert!(witness.verify(&srs, random_element).is_ok());
+
+        let proof_1 = srs.update(PrivateKey::from_u64(252));
+        witness.push(&proof_1, None);
+        let proof_2 = srs.update(PrivateKey::from_u64(512));
+        witness.push(&proof_2, None);
+        assert!(witness.verify(&srs, random_element).is_ok());
+
+        // Swap the pot pubkeys, so the chain is broken at the first contribution
+        let mut bad_witness = witness.clone();
+        bad_witness.pot_pubkeys.swap(1, 2);
+        assert_eq!(
+            bad_witness.verify(&srs, random_element),
+            Err(VerificationError::BrokenChain { index: 0 })
+        );
+
+        // Remove the last contribution, so the chain does not end at the SRS
+        let mut bad_witness = witness.clone();
+        bad_witness.running_products.pop();
+        bad_witness.pot_pubkeys.pop();
+        bad_witness.bls_signatures.pop();
+        assert_eq!(
+            bad_witness.verify(&srs, random_element),
+            Err(VerificationError::FinalPointMismatch)
+        );
+
+        let mut bad_witness = witness.clone();
+        bad_witness.running_products[0] = proof_1.new_accumulated_point;
+        assert_eq!(
+            bad_witness.verify(&srs, random_element),
+            Err(VerificationError::InvalidStartingPoint)
+        );
+
+        let mut bad_witness = witness;
+        bad_witness.bls_signatures.pop();
+        assert_eq!(
+            bad_witness.verify(&srs, random_element),
+            Err(VerificationError::InconsistentWitness)
+        );
+    }
+}